@@ -0,0 +1,266 @@
+#[cfg(test)]
+mod test_cli;
+
+use crate::config::{Config, ConfigError};
+use crate::dry_run::EffectRecorder;
+use crate::entities::{Event, EventPayload};
+use crate::events::process_input_event;
+use crate::response::HandlerResponse;
+use crate::schema::write_schemas;
+use crate::{process_event, Request};
+use lambda_runtime::{Context, Error, LambdaEvent};
+use simple_error::simple_error;
+use std::io::Read;
+use std::path::Path;
+use time::format_description::well_known::iso8601::Iso8601;
+
+const USAGE: &str = "Usage:
+    conductor                         start the Lambda runtime
+    conductor run [FILE|-]            process Request documents and apply their effects
+    conductor plan [FILE|-]           print the effects Request documents would have (dry run)
+    conductor explain [FILE|-]        show how Request documents would be interpreted
+    conductor check-config            validate the configuration and print it
+    conductor schema [OUT_DIR]        write JSON schemas of the public models";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    Run { input: Option<String> },
+    Plan { input: Option<String> },
+    Explain { input: Option<String> },
+    CheckConfig,
+    Schema { out_dir: String },
+    Help,
+}
+
+pub fn parse_command(args: &[String]) -> Result<Option<CliCommand>, Error> {
+    let subcommand = match args.get(1) {
+        Some(subcommand) => subcommand.as_str(),
+        None => return Ok(None),
+    };
+    let operand = args.get(2).cloned();
+    if args.len() > 3 {
+        return Err(_usage_error(&format!(
+            "Too many arguments for {}.",
+            subcommand
+        )));
+    }
+    match subcommand {
+        "run" => Ok(Some(CliCommand::Run { input: operand })),
+        "plan" => Ok(Some(CliCommand::Plan { input: operand })),
+        "explain" => Ok(Some(CliCommand::Explain { input: operand })),
+        "check-config" if operand.is_none() => Ok(Some(CliCommand::CheckConfig)),
+        "check-config" => Err(_usage_error("check-config takes no arguments.")),
+        "schema" => Ok(Some(CliCommand::Schema {
+            out_dir: operand.unwrap_or(String::from("schemas")),
+        })),
+        "help" | "--help" | "-h" => Ok(Some(CliCommand::Help)),
+        other => Err(_usage_error(&format!("Unrecognized subcommand {}.", other))),
+    }
+}
+
+pub async fn run_command(command: CliCommand) -> Result<(), Error> {
+    match command {
+        CliCommand::Schema { out_dir } => {
+            for path in write_schemas(Path::new(&out_dir))? {
+                println!("{}", path.display());
+            }
+            Ok(())
+        }
+        CliCommand::CheckConfig => _check_config().await,
+        CliCommand::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        CliCommand::Run { input } => {
+            let config = _load_config().await?;
+            _run_requests(input, config).await
+        }
+        CliCommand::Plan { input } => {
+            let config = Config {
+                dry_run: true,
+                .._load_config().await?
+            };
+            _run_requests(input, config).await
+        }
+        CliCommand::Explain { input } => {
+            let config = _load_config().await?;
+            _init_tracing(&config);
+            let requests = parse_requests(&_read_input(input.as_deref())?)?;
+            for request in requests {
+                let backend = config.resolve_backend(&request.source, &request.detail.creator_id);
+                let event_id = String::from(&request.event_id);
+                if let Some(reason) = &request.ignored_reason {
+                    println!("{}\n    ignored: {}", event_id, reason);
+                    continue;
+                }
+                match process_input_event(LambdaEvent::new(request, Context::default())).await {
+                    Ok(event) => println!(
+                        "{}\n    backend: {} ({})\n{}",
+                        event_id,
+                        backend.endpoint_prefix,
+                        backend.aws_region,
+                        describe_event(&event)
+                    ),
+                    Err(error) => println!("{}\n    invalid: {:?}", event_id, error),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn parse_requests(input: &str) -> Result<Vec<Request>, Error> {
+    // NOTE: accepts a single document, a JSON array of documents, or
+    // several documents concatenated (e.g. JSON lines).
+    let mut requests = vec![];
+    for (index, value) in serde_json::Deserializer::from_str(input)
+        .into_iter::<serde_json::Value>()
+        .enumerate()
+    {
+        let value = value.map_err(|e| {
+            Box::new(simple_error!(format!(
+                "Failed to parse input document {}:\n{:?}",
+                index, e
+            )))
+        })?;
+        let documents = match value {
+            serde_json::Value::Array(documents) => documents,
+            document => vec![document],
+        };
+        for document in documents {
+            let request = serde_json::from_value::<Request>(document).map_err(|e| {
+                Box::new(simple_error!(format!(
+                    "Input document {} is not a valid request:\n{:?}",
+                    index, e
+                )))
+            })?;
+            requests.push(request);
+        }
+    }
+    Ok(requests)
+}
+
+pub fn describe_event(event: &Event) -> String {
+    let event_time = event
+        .event_time
+        .format(&Iso8601::DEFAULT)
+        .unwrap_or(event.event_time.to_string());
+    let payload = match &event.payload {
+        EventPayload::DataSource { id } => format!("data source {}", id),
+        EventPayload::DataPipeline {
+            id, success_time, ..
+        } => format!(
+            "pipeline {} succeeded at {}",
+            id,
+            success_time
+                .format(&Iso8601::DEFAULT)
+                .unwrap_or(success_time.to_string())
+        ),
+        EventPayload::DataPipelineFailure { id, failure } => format!(
+            "pipeline {} failed with status {:?} ({})",
+            id,
+            failure.status,
+            failure.error.as_deref().unwrap_or("no error")
+        ),
+    };
+    format!(
+        "    source: {}\n    event time: {}\n    payload: {}",
+        event.source, event_time, payload
+    )
+}
+
+async fn _run_requests(input: Option<String>, config: Config) -> Result<(), Error> {
+    _init_tracing(&config);
+    let requests = parse_requests(&_read_input(input.as_deref())?)?;
+    let mut failure_count = 0;
+    for request in requests {
+        let event_id = String::from(&request.event_id);
+        let recorder = config.dry_run.then(EffectRecorder::new);
+        let event = LambdaEvent::new(request, Context::default());
+        let result = process_event(event, &config, recorder.as_ref(), false).await;
+        match HandlerResponse::from_result(&event_id, result) {
+            Ok(response) => {
+                let response = HandlerResponse {
+                    plan: recorder.map(|recorder| recorder.into_plan(&event_id)),
+                    ..response
+                };
+                if !response.is_success() {
+                    failure_count += 1;
+                }
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            }
+            Err(error) => {
+                failure_count += 1;
+                println!("{}\tfailed\t{}", event_id, error);
+            }
+        }
+    }
+    if failure_count > 0 {
+        Err(Box::new(simple_error!(format!(
+            "{} requests failed to process.",
+            failure_count
+        ))))
+    } else {
+        Ok(())
+    }
+}
+
+async fn _check_config() -> Result<(), Error> {
+    match Config::load().await {
+        Ok(config) => {
+            println!("Configuration is valid.\n{:#?}", config);
+            Ok(())
+        }
+        Err(ConfigError::ValidationErrors(errors)) => {
+            for error in &errors {
+                println!("error: {}", error);
+            }
+            Err(Box::new(simple_error!(format!(
+                "Configuration has {} errors.",
+                errors.len()
+            ))))
+        }
+        Err(error) => Err(Box::new(simple_error!(format!(
+            "Invalid configuration:\n{:?}",
+            error
+        )))),
+    }
+}
+
+async fn _load_config() -> Result<Config, Error> {
+    Config::load()
+        .await
+        .map_err(|e| Box::new(simple_error!(format!("Invalid configuration:\n{:?}", e))).into())
+}
+
+fn _init_tracing(config: &Config) {
+    tracing_subscriber::fmt()
+        .with_max_level(config.log_level)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+fn _read_input(input: Option<&str>) -> Result<String, Error> {
+    let mut contents = String::new();
+    match input {
+        None | Some("-") => {
+            std::io::stdin()
+                .read_to_string(&mut contents)
+                .map_err(|e| Box::new(simple_error!(format!("Failed to read stdin:\n{:?}", e))))?;
+        }
+        Some(path) => {
+            contents = std::fs::read_to_string(path).map_err(|e| {
+                Box::new(simple_error!(format!(
+                    "Failed to read input file {}:\n{:?}",
+                    path, e
+                )))
+            })?;
+        }
+    }
+    Ok(contents)
+}
+
+fn _usage_error(mssg: &str) -> Error {
+    Box::new(simple_error!(format!("{}\n{}", mssg, USAGE))).into()
+}
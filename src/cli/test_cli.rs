@@ -0,0 +1,110 @@
+use super::*;
+use serde_json::json;
+
+fn _build_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| String::from(*arg)).collect()
+}
+
+fn _build_request_json(event_id: &str) -> serde_json::Value {
+    json!({
+        "source": "conductor.test",
+        "event_id": event_id,
+        "detail": {
+            "schema_version": 2,
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {
+                "event_time": "2023-09-21T10:05:00Z"
+            }
+        }
+    })
+}
+
+#[test]
+fn parse_command_test_lambda_mode_without_arguments() {
+    assert_eq!(parse_command(&_build_args(&["conductor"])).unwrap(), None);
+}
+
+#[test]
+fn parse_command_test_subcommands() {
+    assert_eq!(
+        parse_command(&_build_args(&["conductor", "run", "requests.json"])).unwrap(),
+        Some(CliCommand::Run {
+            input: Some(String::from("requests.json"))
+        })
+    );
+    assert_eq!(
+        parse_command(&_build_args(&["conductor", "plan", "-"])).unwrap(),
+        Some(CliCommand::Plan {
+            input: Some(String::from("-"))
+        })
+    );
+    assert_eq!(
+        parse_command(&_build_args(&["conductor", "explain"])).unwrap(),
+        Some(CliCommand::Explain { input: None })
+    );
+    assert_eq!(
+        parse_command(&_build_args(&["conductor", "check-config"])).unwrap(),
+        Some(CliCommand::CheckConfig)
+    );
+    assert_eq!(
+        parse_command(&_build_args(&["conductor", "schema"])).unwrap(),
+        Some(CliCommand::Schema {
+            out_dir: String::from("schemas")
+        })
+    );
+}
+
+#[test]
+fn parse_command_test_rejects_bad_arguments() {
+    assert!(parse_command(&_build_args(&["conductor", "deploy"])).is_err());
+    assert!(parse_command(&_build_args(&["conductor", "check-config", "x"])).is_err());
+    assert!(parse_command(&_build_args(&["conductor", "run", "a", "b"])).is_err());
+}
+
+#[test]
+fn parse_requests_test_single_array_and_concatenated_documents() {
+    let event_ids: Vec<String> = (0..3)
+        .map(|_| format!("event{}", rand::random::<u32>()))
+        .collect();
+    let single = _build_request_json(&event_ids[0]).to_string();
+    assert_eq!(parse_requests(&single).unwrap().len(), 1);
+    let array = json!([
+        _build_request_json(&event_ids[0]),
+        _build_request_json(&event_ids[1])
+    ])
+    .to_string();
+    assert_eq!(parse_requests(&array).unwrap().len(), 2);
+    let lines = event_ids
+        .iter()
+        .map(|event_id| _build_request_json(event_id).to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+    let requests = parse_requests(&lines).unwrap();
+    assert_eq!(
+        requests
+            .iter()
+            .map(|request| String::from(&request.event_id))
+            .collect::<Vec<String>>(),
+        event_ids
+    );
+}
+
+#[test]
+fn parse_requests_test_rejects_invalid_documents() {
+    assert!(parse_requests("{\"source\": ").is_err());
+    assert!(parse_requests(&json!({"source": "conductor.test"}).to_string()).is_err());
+}
+
+#[tokio::test]
+async fn describe_event_test_data_source() {
+    let request = parse_requests(&_build_request_json("event1").to_string())
+        .unwrap()
+        .remove(0);
+    let event = process_input_event(LambdaEvent::new(request, Context::default()))
+        .await
+        .unwrap();
+    let description = describe_event(&event);
+    assert!(description.contains("source: conductor.test"));
+    assert!(description.contains("payload: data source source1"));
+}
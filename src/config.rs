@@ -1,4 +1,559 @@
+#[cfg(test)]
+pub(crate) mod test_config;
+
+use crate::event_sources::s3::S3SourceMappingRule;
+use crate::pipeline_effects::rules::TriggerRuleRegistry;
+use crate::pipeline_effects::FailurePolicy;
+use crate::routing::{distinct_backends, resolve_backend, Backend, TenantRoute};
+use crate::signing::{get_credentials_cache, get_credentials_provider};
+use aws_config::retry::RetryConfig;
+use aws_config::timeout::TimeoutConfig;
+use aws_credential_types::cache::SharedCredentialsCache;
+use aws_credential_types::provider::SharedCredentialsProvider;
+use aws_types::region::Region;
+use aws_types::SdkConfig;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::Level;
+use url::Url;
+
+const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_SFN_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+const DEFAULT_DEADLINE_MARGIN_SECS: u64 = 3;
+const DEFAULT_MAX_PIPELINE_RETRIES: u32 = 3;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    ConfigFileError(String),
+    ValidationErrors(Vec<String>),
+    ClientBuildingError(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandlerMode {
+    Direct,
+    Sqs,
+    S3,
+    Schedule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DedupStoreKind {
+    Memory,
+    File(PathBuf),
+    Api,
+}
+
+#[derive(Debug, Clone)]
 pub struct Config {
     pub endpoint_prefix: String,
     pub aws_region: String,
+    pub log_level: Level,
+    pub http_timeout: Duration,
+    pub sfn_max_attempts: u32,
+    pub max_concurrency: usize,
+    pub deadline_margin: Duration,
+    pub handler_mode: HandlerMode,
+    pub dedup_store: DedupStoreKind,
+    pub s3_source_mapping: Vec<S3SourceMappingRule>,
+    pub default_failure_policy: FailurePolicy,
+    pub max_pipeline_retries: u32,
+    pub tenant_routes: Vec<TenantRoute>,
+    pub trigger_rules: TriggerRuleRegistry,
+    pub refresh_expired_permits: bool,
+    pub dry_run: bool,
+    pub clients: Clients,
+}
+
+// NOTE: built once at cold start so every invocation reuses the same
+// connection pools and resolved AWS configuration.
+#[derive(Debug, Clone)]
+pub struct Clients {
+    pub http: Client,
+    backends: Vec<BackendClients>,
+}
+
+// NOTE: built once per distinct backend, so the credentials (and any role
+// assumed for them) are shared by every request to that backend.
+#[derive(Debug, Clone)]
+struct BackendClients {
+    backend: Backend,
+    sfn: aws_sdk_sfn::Client,
+    credentials: Option<SharedCredentialsCache>,
+}
+
+impl Clients {
+    pub fn sfn(&self, backend: &Backend) -> &aws_sdk_sfn::Client {
+        &self._backend_clients(backend).sfn
+    }
+
+    pub fn credentials(&self, backend: &Backend) -> Option<&SharedCredentialsCache> {
+        self._backend_clients(backend).credentials.as_ref()
+    }
+
+    fn _backend_clients(&self, backend: &Backend) -> &BackendClients {
+        // NOTE: there are clients for every backend the config resolves
+        // to, the first ones being the default backend's.
+        self.backends
+            .iter()
+            .find(|clients| &clients.backend == backend)
+            .unwrap_or(&self.backends[0])
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RawConfig {
+    endpoint_url: Option<String>,
+    aws_region: Option<String>,
+    log_level: Option<String>,
+    http_timeout_secs: Option<u64>,
+    sfn_max_attempts: Option<u32>,
+    max_concurrency: Option<usize>,
+    deadline_margin_secs: Option<u64>,
+    handler_mode: Option<String>,
+    dedup_store: Option<String>,
+    dedup_file_path: Option<String>,
+    s3_source_mapping: Option<Vec<S3SourceMappingRule>>,
+    default_failure_policy: Option<String>,
+    max_pipeline_retries: Option<u32>,
+    tenant_routes: Option<Vec<TenantRoute>>,
+    refresh_expired_permits: Option<bool>,
+    dry_run: Option<bool>,
+}
+
+impl Config {
+    pub async fn load() -> Result<Config, ConfigError> {
+        let env_vars: HashMap<String, String> = std::env::vars().collect();
+        let file_config = match env_vars.get("ENV_CONFIG_FILE") {
+            Some(path) => Some(_read_config_file(path)?),
+            None => None,
+        };
+        let sdk_config = aws_config::from_env().load().await;
+        Config::from_sources(file_config, &env_vars, &sdk_config)
+    }
+
+    pub(crate) fn from_sources(
+        file_config: Option<RawConfig>,
+        env_vars: &HashMap<String, String>,
+        sdk_config: &SdkConfig,
+    ) -> Result<Config, ConfigError> {
+        let mut errors = vec![];
+        let raw = _overlay_env_vars(file_config.unwrap_or_default(), env_vars, &mut errors);
+        let endpoint_prefix = _validate_endpoint_url(&raw.endpoint_url, &mut errors);
+        let aws_region = _validate_aws_region(&raw.aws_region, &mut errors);
+        let log_level = match raw.log_level.as_deref().unwrap_or("INFO").parse::<Level>() {
+            Ok(level) => level,
+            Err(_) => {
+                errors.push(format!(
+                    "log_level {:?} is not one of TRACE, DEBUG, INFO, WARN or ERROR.",
+                    raw.log_level
+                ));
+                Level::INFO
+            }
+        };
+        let http_timeout_secs = raw.http_timeout_secs.unwrap_or(DEFAULT_HTTP_TIMEOUT_SECS);
+        if http_timeout_secs == 0 {
+            errors.push(String::from("http_timeout_secs must be greater than 0."));
+        }
+        let sfn_max_attempts = raw.sfn_max_attempts.unwrap_or(DEFAULT_SFN_MAX_ATTEMPTS);
+        if sfn_max_attempts == 0 {
+            errors.push(String::from("sfn_max_attempts must be greater than 0."));
+        }
+        let max_concurrency = raw.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY);
+        if max_concurrency == 0 {
+            errors.push(String::from("max_concurrency must be greater than 0."));
+        }
+        let deadline_margin_secs = raw
+            .deadline_margin_secs
+            .unwrap_or(DEFAULT_DEADLINE_MARGIN_SECS);
+        let handler_mode = match raw
+            .handler_mode
+            .as_deref()
+            .unwrap_or("DIRECT")
+            .to_uppercase()
+            .as_str()
+        {
+            "DIRECT" => HandlerMode::Direct,
+            "SQS" => HandlerMode::Sqs,
+            "S3" => HandlerMode::S3,
+            "SCHEDULE" => HandlerMode::Schedule,
+            other => {
+                errors.push(format!(
+                    "handler_mode {} is not one of DIRECT, SQS, S3 or SCHEDULE.",
+                    other
+                ));
+                HandlerMode::Direct
+            }
+        };
+        let dedup_store = match raw
+            .dedup_store
+            .as_deref()
+            .unwrap_or("MEMORY")
+            .to_uppercase()
+            .as_str()
+        {
+            "MEMORY" => DedupStoreKind::Memory,
+            "FILE" => match &raw.dedup_file_path {
+                Some(path) => DedupStoreKind::File(PathBuf::from(path)),
+                None => {
+                    errors.push(String::from(
+                        "dedup_file_path is required when dedup_store is FILE.",
+                    ));
+                    DedupStoreKind::Memory
+                }
+            },
+            "API" => DedupStoreKind::Api,
+            other => {
+                errors.push(format!(
+                    "dedup_store {} is not one of MEMORY, FILE or API.",
+                    other
+                ));
+                DedupStoreKind::Memory
+            }
+        };
+        let default_failure_policy = match raw
+            .default_failure_policy
+            .as_deref()
+            .unwrap_or("NOTIFY")
+            .to_uppercase()
+            .as_str()
+        {
+            "RETRY" => FailurePolicy::Retry,
+            "NOTIFY" => FailurePolicy::Notify,
+            "BLOCK" => FailurePolicy::Block,
+            other => {
+                errors.push(format!(
+                    "default_failure_policy {} is not one of RETRY, NOTIFY or BLOCK.",
+                    other
+                ));
+                FailurePolicy::Notify
+            }
+        };
+        let tenant_routes = raw.tenant_routes.unwrap_or_default();
+        for (index, route) in tenant_routes.iter().enumerate() {
+            _validate_tenant_route(index, route, &mut errors);
+        }
+        if !errors.is_empty() {
+            return Err(ConfigError::ValidationErrors(errors));
+        }
+        let http_timeout = Duration::from_secs(http_timeout_secs);
+        let http_client = Client::builder()
+            .timeout(http_timeout)
+            .build()
+            .map_err(|e| {
+                ConfigError::ClientBuildingError(format!("Failed to build http client:\n{:?}", e))
+            })?;
+        let default_backend = Backend {
+            endpoint_prefix: String::from(&endpoint_prefix),
+            aws_region: String::from(&aws_region),
+            credentials_profile: None,
+            role_arn: None,
+        };
+        let backend_clients = distinct_backends(&tenant_routes, &default_backend)
+            .into_iter()
+            .map(|backend| {
+                let credentials_provider = get_credentials_provider(
+                    sdk_config,
+                    backend.credentials_profile.as_deref(),
+                    backend.role_arn.as_deref(),
+                    &backend.aws_region,
+                );
+                BackendClients {
+                    sfn: _build_sfn_client(
+                        sdk_config,
+                        &backend,
+                        credentials_provider.clone(),
+                        http_timeout,
+                        sfn_max_attempts,
+                    ),
+                    credentials: credentials_provider
+                        .map(|provider| get_credentials_cache(sdk_config, provider)),
+                    backend,
+                }
+            })
+            .collect();
+        Ok(Config {
+            endpoint_prefix,
+            aws_region,
+            log_level,
+            http_timeout,
+            sfn_max_attempts,
+            max_concurrency,
+            deadline_margin: Duration::from_secs(deadline_margin_secs),
+            handler_mode,
+            dedup_store,
+            s3_source_mapping: raw.s3_source_mapping.unwrap_or_default(),
+            default_failure_policy,
+            max_pipeline_retries: raw
+                .max_pipeline_retries
+                .unwrap_or(DEFAULT_MAX_PIPELINE_RETRIES),
+            tenant_routes,
+            trigger_rules: TriggerRuleRegistry::with_builtins(),
+            refresh_expired_permits: raw.refresh_expired_permits.unwrap_or(false),
+            dry_run: raw.dry_run.unwrap_or(false),
+            clients: Clients {
+                http: http_client,
+                backends: backend_clients,
+            },
+        })
+    }
+
+    // NOTE: replaces the builtin trigger rules; start from
+    // TriggerRuleRegistry::with_builtins() to keep them alongside custom ones.
+    pub fn with_trigger_rules(self, trigger_rules: TriggerRuleRegistry) -> Config {
+        Config {
+            trigger_rules,
+            ..self
+        }
+    }
+
+    pub fn default_backend(&self) -> Backend {
+        Backend {
+            endpoint_prefix: String::from(&self.endpoint_prefix),
+            aws_region: String::from(&self.aws_region),
+            credentials_profile: None,
+            role_arn: None,
+        }
+    }
+
+    pub fn resolve_backend(&self, source: &str, creator_id: &str) -> Backend {
+        resolve_backend(
+            &self.tenant_routes,
+            &self.default_backend(),
+            source,
+            creator_id,
+        )
+    }
+
+    pub fn backends(&self) -> Vec<Backend> {
+        distinct_backends(&self.tenant_routes, &self.default_backend())
+    }
+}
+
+fn _build_sfn_client(
+    sdk_config: &SdkConfig,
+    backend: &Backend,
+    credentials_provider: Option<SharedCredentialsProvider>,
+    http_timeout: Duration,
+    sfn_max_attempts: u32,
+) -> aws_sdk_sfn::Client {
+    let mut sfn_config = aws_sdk_sfn::config::Builder::from(sdk_config)
+        .region(Region::new(String::from(&backend.aws_region)))
+        .retry_config(RetryConfig::standard().with_max_attempts(sfn_max_attempts))
+        .timeout_config(
+            TimeoutConfig::builder()
+                .operation_timeout(http_timeout)
+                .build(),
+        );
+    sfn_config.set_credentials_provider(credentials_provider);
+    aws_sdk_sfn::Client::from_conf(sfn_config.build())
+}
+
+fn _read_config_file(path: &str) -> Result<RawConfig, ConfigError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ConfigError::ConfigFileError(format!("Failed to read config file {}:\n{:?}", path, e))
+    })?;
+    if path.ends_with(".toml") {
+        toml::from_str::<RawConfig>(&contents).map_err(|e| {
+            ConfigError::ConfigFileError(format!("Failed to parse config file {}:\n{}", path, e))
+        })
+    } else if path.ends_with(".json") {
+        serde_json::from_str::<RawConfig>(&contents).map_err(|e| {
+            ConfigError::ConfigFileError(format!("Failed to parse config file {}:\n{}", path, e))
+        })
+    } else {
+        Err(ConfigError::ConfigFileError(format!(
+            "Config file {} must have a .toml or .json extension.",
+            path
+        )))
+    }
+}
+
+fn _overlay_env_vars(
+    raw: RawConfig,
+    env_vars: &HashMap<String, String>,
+    errors: &mut Vec<String>,
+) -> RawConfig {
+    let get = |name: &str| env_vars.get(name).cloned();
+    let http_timeout_secs =
+        _parse_env_number(env_vars, "ENV_HTTP_TIMEOUT_SECS", errors).or(raw.http_timeout_secs);
+    let sfn_max_attempts =
+        _parse_env_number(env_vars, "ENV_SFN_MAX_ATTEMPTS", errors).or(raw.sfn_max_attempts);
+    let max_concurrency =
+        _parse_env_number(env_vars, "ENV_MAX_CONCURRENCY", errors).or(raw.max_concurrency);
+    let deadline_margin_secs = _parse_env_number(env_vars, "ENV_DEADLINE_MARGIN_SECS", errors)
+        .or(raw.deadline_margin_secs);
+    let max_pipeline_retries = _parse_env_number(env_vars, "ENV_MAX_PIPELINE_RETRIES", errors)
+        .or(raw.max_pipeline_retries);
+    let mut parse_flag = |name: &str| match env_vars.get(name) {
+        Some(value) => match value.to_lowercase().as_str() {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => {
+                errors.push(format!("Env var {} must be true or false.", name));
+                None
+            }
+        },
+        None => None,
+    };
+    let refresh_expired_permits =
+        parse_flag("ENV_REFRESH_EXPIRED_PERMITS").or(raw.refresh_expired_permits);
+    let dry_run = parse_flag("ENV_DRY_RUN").or(raw.dry_run);
+    let tenant_routes = match env_vars.get("ENV_TENANT_ROUTES") {
+        Some(routes_json) => match serde_json::from_str::<Vec<TenantRoute>>(routes_json) {
+            Ok(routes) => Some(routes),
+            Err(e) => {
+                errors.push(format!(
+                    "Env var ENV_TENANT_ROUTES is not a valid routing table: {}",
+                    e
+                ));
+                None
+            }
+        },
+        None => raw.tenant_routes,
+    };
+    let s3_source_mapping = match env_vars.get("ENV_S3_SOURCE_MAPPING") {
+        Some(mapping_json) => {
+            match serde_json::from_str::<Vec<S3SourceMappingRule>>(mapping_json) {
+                Ok(rules) => Some(rules),
+                Err(e) => {
+                    errors.push(format!(
+                        "Env var ENV_S3_SOURCE_MAPPING is not a valid mapping: {}",
+                        e
+                    ));
+                    None
+                }
+            }
+        }
+        None => raw.s3_source_mapping,
+    };
+    RawConfig {
+        endpoint_url: get("ENV_ENDPOINT_URL").or(raw.endpoint_url),
+        aws_region: get("ENV_AWS_REGION").or(raw.aws_region),
+        log_level: get("ENV_LOG_LEVEL").or(raw.log_level),
+        http_timeout_secs,
+        sfn_max_attempts,
+        max_concurrency,
+        deadline_margin_secs,
+        handler_mode: get("ENV_HANDLER_MODE").or(raw.handler_mode),
+        dedup_store: get("ENV_DEDUP_STORE").or(raw.dedup_store),
+        dedup_file_path: get("ENV_DEDUP_FILE_PATH").or(raw.dedup_file_path),
+        s3_source_mapping,
+        default_failure_policy: get("ENV_DEFAULT_FAILURE_POLICY").or(raw.default_failure_policy),
+        max_pipeline_retries,
+        tenant_routes,
+        refresh_expired_permits,
+        dry_run,
+    }
+}
+
+fn _parse_env_number<T: FromStr>(
+    env_vars: &HashMap<String, String>,
+    name: &str,
+    errors: &mut Vec<String>,
+) -> Option<T> {
+    // NOTE: parsing into the target type directly rejects values that are
+    // out of its range instead of truncating them.
+    match env_vars.get(name) {
+        Some(value) => match value.parse() {
+            Ok(number) => Some(number),
+            Err(_) => {
+                errors.push(format!(
+                    "Env var {} must be a positive integer that fits in {}.",
+                    name,
+                    std::any::type_name::<T>()
+                ));
+                None
+            }
+        },
+        None => None,
+    }
+}
+
+fn _validate_tenant_route(index: usize, route: &TenantRoute, errors: &mut Vec<String>) {
+    let mut route_errors = vec![];
+    match (&route.source, &route.creator_id_prefix) {
+        (Some(_), None) | (None, Some(_)) => {}
+        _ => route_errors.push(String::from(
+            "exactly one of source or creator_id_prefix must be set.",
+        )),
+    }
+    if let Some(role_arn) = &route.role_arn {
+        if !role_arn.starts_with("arn:") || !role_arn.contains(":role/") {
+            route_errors.push(format!("role_arn {} is not an IAM role ARN.", role_arn));
+        }
+    }
+    _validate_endpoint_url(&Some(String::from(&route.endpoint_url)), &mut route_errors);
+    _validate_aws_region(&Some(String::from(&route.aws_region)), &mut route_errors);
+    for route_error in route_errors {
+        errors.push(format!("tenant_routes[{}]: {}", index, route_error));
+    }
+}
+
+fn _validate_endpoint_url(maybe_url: &Option<String>, errors: &mut Vec<String>) -> String {
+    match maybe_url {
+        Some(url_string) => match Url::parse(url_string) {
+            Ok(url) if url.scheme() == "https" || url.scheme() == "http" => {
+                String::from(url_string)
+            }
+            Ok(url) => {
+                errors.push(format!(
+                    "endpoint_url {} must use http or https, not {}.",
+                    url_string,
+                    url.scheme()
+                ));
+                String::new()
+            }
+            Err(e) => {
+                errors.push(format!(
+                    "endpoint_url {} is not a valid url: {}",
+                    url_string, e
+                ));
+                String::new()
+            }
+        },
+        None => {
+            errors.push(String::from(
+                "endpoint_url is undefined; set ENV_ENDPOINT_URL or endpoint_url in the config file.",
+            ));
+            String::new()
+        }
+    }
+}
+
+fn _validate_aws_region(maybe_region: &Option<String>, errors: &mut Vec<String>) -> String {
+    match maybe_region {
+        Some(region) => {
+            // e.g. us-east-1, eu-central-2, us-gov-west-1, ap-southeast-3
+            let parts: Vec<&str> = region.split('-').collect();
+            let is_valid = parts.len() >= 3
+                && parts.iter().all(|part| {
+                    !part.is_empty()
+                        && part
+                            .chars()
+                            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+                })
+                && parts[parts.len() - 1].chars().all(|c| c.is_ascii_digit())
+                && parts[0].chars().all(|c| c.is_ascii_lowercase());
+            if !is_valid {
+                errors.push(format!(
+                    "aws_region {} does not look like an AWS region.",
+                    region
+                ));
+            }
+            String::from(region)
+        }
+        None => {
+            errors.push(String::from(
+                "aws_region is undefined; set ENV_AWS_REGION or aws_region in the config file.",
+            ));
+            String::new()
+        }
+    }
 }
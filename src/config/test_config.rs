@@ -0,0 +1,438 @@
+use super::*;
+use crate::{TriggerRule, TriggerRuleParameters};
+use aws_credential_types::cache::ProvideCachedCredentials;
+use aws_credential_types::provider::{future, ProvideCredentials};
+use aws_credential_types::Credentials;
+use aws_sdk_sfn::config::{AsyncSleep, SharedAsyncSleep, Sleep};
+use time::OffsetDateTime;
+
+#[derive(Debug)]
+struct _TokioSleep;
+
+impl AsyncSleep for _TokioSleep {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Sleep::new(tokio::time::sleep(duration))
+    }
+}
+
+// NOTE: the loaded SDK config always has a sleep implementation, which the
+// SFN clients need for their retries and timeouts.
+pub(crate) fn build_sdk_config() -> SdkConfig {
+    SdkConfig::builder()
+        .sleep_impl(SharedAsyncSleep::new(_TokioSleep))
+        .build()
+}
+
+fn _build_env_vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (String::from(*name), String::from(*value)))
+        .collect()
+}
+
+fn _write_config_file(extension: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!(
+        "conductor-config-{}.{}",
+        rand::random::<u32>(),
+        extension
+    ));
+    std::fs::write(&path, contents).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn _validation_errors(result: Result<Config, ConfigError>) -> Vec<String> {
+    match result {
+        Err(ConfigError::ValidationErrors(errors)) => errors,
+        other => panic!("Expected validation errors, got {:?}", other),
+    }
+}
+
+#[test]
+fn config_test_from_env_vars() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/api/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+        ("ENV_LOG_LEVEL", "debug"),
+        ("ENV_HTTP_TIMEOUT_SECS", "30"),
+        ("ENV_SFN_MAX_ATTEMPTS", "5"),
+        ("ENV_MAX_CONCURRENCY", "16"),
+        ("ENV_DEADLINE_MARGIN_SECS", "5"),
+        ("ENV_MAX_PIPELINE_RETRIES", "7"),
+        ("ENV_HANDLER_MODE", "sqs"),
+        ("ENV_DEDUP_STORE", "FILE"),
+        ("ENV_DEDUP_FILE_PATH", "/tmp/outcomes.json"),
+        (
+            "ENV_S3_SOURCE_MAPPING",
+            r#"[{"bucket": "landing", "prefix": "raw/", "data_source_id": "raw"}]"#,
+        ),
+        ("ENV_DEFAULT_FAILURE_POLICY", "BLOCK"),
+    ]);
+    let config = Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap();
+    assert_eq!(config.endpoint_prefix, "https://example.com/api/");
+    assert_eq!(config.aws_region, "eu-west-1");
+    assert_eq!(config.log_level, Level::DEBUG);
+    assert_eq!(config.http_timeout, Duration::from_secs(30));
+    assert_eq!(config.sfn_max_attempts, 5);
+    assert_eq!(config.max_concurrency, 16);
+    assert_eq!(config.max_pipeline_retries, 7);
+    assert_eq!(config.deadline_margin, Duration::from_secs(5));
+    assert_eq!(config.handler_mode, HandlerMode::Sqs);
+    assert_eq!(
+        config.dedup_store,
+        DedupStoreKind::File(PathBuf::from("/tmp/outcomes.json"))
+    );
+    assert_eq!(config.s3_source_mapping.len(), 1);
+    assert!(matches!(
+        config.default_failure_policy,
+        FailurePolicy::Block
+    ));
+}
+
+#[test]
+fn config_test_defaults() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "http://localhost:8080/"),
+        ("ENV_AWS_REGION", "us-east-1"),
+    ]);
+    let config = Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap();
+    assert_eq!(config.log_level, Level::INFO);
+    assert_eq!(
+        config.http_timeout,
+        Duration::from_secs(DEFAULT_HTTP_TIMEOUT_SECS)
+    );
+    assert_eq!(config.sfn_max_attempts, DEFAULT_SFN_MAX_ATTEMPTS);
+    assert_eq!(config.max_concurrency, DEFAULT_MAX_CONCURRENCY);
+    assert_eq!(config.max_pipeline_retries, DEFAULT_MAX_PIPELINE_RETRIES);
+    assert_eq!(
+        config.deadline_margin,
+        Duration::from_secs(DEFAULT_DEADLINE_MARGIN_SECS)
+    );
+    assert_eq!(config.handler_mode, HandlerMode::Direct);
+    assert_eq!(config.dedup_store, DedupStoreKind::Memory);
+    assert!(config.s3_source_mapping.is_empty());
+    assert!(matches!(
+        config.default_failure_policy,
+        FailurePolicy::Notify
+    ));
+}
+
+#[test]
+fn config_test_collects_all_validation_errors() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "ftp://example.com/"),
+        ("ENV_AWS_REGION", "not a region"),
+        ("ENV_LOG_LEVEL", "LOUD"),
+        ("ENV_HTTP_TIMEOUT_SECS", "-1"),
+        ("ENV_HANDLER_MODE", "KAFKA"),
+        ("ENV_DEFAULT_FAILURE_POLICY", "IGNORE"),
+    ]);
+    let errors = _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config()));
+    assert_eq!(errors.len(), 6, "{:?}", errors);
+}
+
+#[test]
+fn config_test_missing_required_values() {
+    let errors = _validation_errors(Config::from_sources(
+        None,
+        &HashMap::new(),
+        &build_sdk_config(),
+    ));
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+}
+
+#[test]
+fn config_test_file_dedup_store_requires_path() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/"),
+        ("ENV_AWS_REGION", "eu-central-1"),
+        ("ENV_DEDUP_STORE", "FILE"),
+    ]);
+    let errors = _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config()));
+    assert_eq!(
+        errors,
+        vec![String::from(
+            "dedup_file_path is required when dedup_store is FILE."
+        )]
+    );
+}
+
+#[test]
+fn config_test_toml_file_with_env_var_precedence() {
+    let path = _write_config_file(
+        "toml",
+        r#"
+endpoint_url = "https://example.com/"
+aws_region = "eu-west-1"
+handler_mode = "SCHEDULE"
+sfn_max_attempts = 7
+
+[[s3_source_mapping]]
+bucket = "landing"
+data_source_id = "landing_all"
+"#,
+    );
+    let file_config = _read_config_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let env_vars = _build_env_vars(&[("ENV_AWS_REGION", "us-west-2")]);
+    let config = Config::from_sources(Some(file_config), &env_vars, &build_sdk_config()).unwrap();
+    assert_eq!(config.endpoint_prefix, "https://example.com/");
+    assert_eq!(config.aws_region, "us-west-2");
+    assert_eq!(config.handler_mode, HandlerMode::Schedule);
+    assert_eq!(config.sfn_max_attempts, 7);
+    assert_eq!(config.s3_source_mapping.len(), 1);
+}
+
+#[test]
+fn config_test_json_file() {
+    let path = _write_config_file(
+        "json",
+        r#"{"endpoint_url": "https://example.com/", "aws_region": "ap-southeast-2", "dedup_store": "API"}"#,
+    );
+    let file_config = _read_config_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let config =
+        Config::from_sources(Some(file_config), &HashMap::new(), &build_sdk_config()).unwrap();
+    assert_eq!(config.aws_region, "ap-southeast-2");
+    assert_eq!(config.dedup_store, DedupStoreKind::Api);
+}
+
+#[test]
+fn config_test_file_rejects_unknown_keys() {
+    let path = _write_config_file("toml", "endpoint_uri = \"https://example.com/\"\n");
+    let result = _read_config_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(ConfigError::ConfigFileError(_))));
+}
+
+#[test]
+fn config_test_file_rejects_unknown_extension() {
+    assert!(matches!(
+        _read_config_file("/tmp/config.yaml"),
+        Err(ConfigError::ConfigFileError(_))
+    ));
+}
+
+#[test]
+fn config_test_tenant_routes_from_env_var() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://default.example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+        (
+            "ENV_TENANT_ROUTES",
+            r#"[
+                {"source": "com.sales", "endpoint_url": "https://sales.example.com/", "aws_region": "us-east-1", "credentials_profile": "sales"},
+                {"creator_id_prefix": "finance_", "endpoint_url": "https://finance.example.com/", "aws_region": "eu-central-1"}
+            ]"#,
+        ),
+    ]);
+    let config = Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap();
+    assert_eq!(config.tenant_routes.len(), 2);
+    assert_eq!(config.backends().len(), 3);
+    assert_eq!(config.clients.backends.len(), 3);
+    let backend = config.resolve_backend("com.finance", "finance_ledger");
+    assert_eq!(backend.endpoint_prefix, "https://finance.example.com/");
+    assert_eq!(backend.aws_region, "eu-central-1");
+    assert_eq!(
+        config.resolve_backend("com.marketing", "campaigns"),
+        config.default_backend()
+    );
+}
+
+#[test]
+fn config_test_invalid_tenant_routes() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://default.example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+        (
+            "ENV_TENANT_ROUTES",
+            r#"[
+                {"endpoint_url": "https://sales.example.com/", "aws_region": "us-east-1"},
+                {"source": "com.finance", "endpoint_url": "ftp://finance.example.com/", "aws_region": "somewhere"}
+            ]"#,
+        ),
+    ]);
+    let errors = _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config()));
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].starts_with("tenant_routes[0]: "));
+    assert!(errors[1].starts_with("tenant_routes[1]: "));
+}
+
+#[test]
+fn config_test_tenant_routes_with_role_arn() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://default.example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+        (
+            "ENV_TENANT_ROUTES",
+            r#"[
+                {"source": "com.sales", "endpoint_url": "https://sales.example.com/", "aws_region": "us-east-1", "role_arn": "arn:aws:iam::123456789012:role/conductor-sales"},
+                {"source": "com.finance", "endpoint_url": "https://finance.example.com/", "aws_region": "us-east-1", "role_arn": "conductor-finance"}
+            ]"#,
+        ),
+    ]);
+    let errors = _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config()));
+    assert_eq!(
+        errors,
+        vec![String::from(
+            "tenant_routes[1]: role_arn conductor-finance is not an IAM role ARN."
+        )]
+    );
+}
+
+#[derive(Debug, Default)]
+struct _CountingCredentials {
+    calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl ProvideCredentials for _CountingCredentials {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        future::ProvideCredentials::ready(Ok(Credentials::new(
+            "akid", "secret", None, None, "counting",
+        )))
+    }
+}
+
+#[tokio::test]
+async fn config_test_backend_credentials_are_cached() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+    ]);
+    let provider = _CountingCredentials::default();
+    let calls = provider.calls.clone();
+    let sdk_config = SdkConfig::builder()
+        .sleep_impl(SharedAsyncSleep::new(_TokioSleep))
+        .credentials_provider(SharedCredentialsProvider::new(provider))
+        .build();
+    let config = Config::from_sources(None, &env_vars, &sdk_config).unwrap();
+    let backend = config.default_backend();
+    for _ in 0..3 {
+        config
+            .clients
+            .credentials(&backend)
+            .unwrap()
+            .provide_cached_credentials()
+            .await
+            .unwrap();
+    }
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+fn config_test_dry_run() {
+    let mut env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+    ]);
+    assert!(
+        !Config::from_sources(None, &env_vars, &build_sdk_config())
+            .unwrap()
+            .dry_run
+    );
+    env_vars.insert(String::from("ENV_DRY_RUN"), String::from("true"));
+    assert!(
+        Config::from_sources(None, &env_vars, &build_sdk_config())
+            .unwrap()
+            .dry_run
+    );
+    env_vars.insert(String::from("ENV_DRY_RUN"), String::from("maybe"));
+    assert_eq!(
+        _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config())).len(),
+        1
+    );
+}
+
+#[test]
+fn config_test_refresh_expired_permits() {
+    let mut env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+    ]);
+    assert!(
+        !Config::from_sources(None, &env_vars, &build_sdk_config())
+            .unwrap()
+            .refresh_expired_permits
+    );
+    env_vars.insert(
+        String::from("ENV_REFRESH_EXPIRED_PERMITS"),
+        String::from("1"),
+    );
+    assert!(
+        Config::from_sources(None, &env_vars, &build_sdk_config())
+            .unwrap()
+            .refresh_expired_permits
+    );
+    env_vars.insert(
+        String::from("ENV_REFRESH_EXPIRED_PERMITS"),
+        String::from("sometimes"),
+    );
+    assert_eq!(
+        _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config())),
+        vec![String::from(
+            "Env var ENV_REFRESH_EXPIRED_PERMITS must be true or false."
+        )]
+    );
+}
+
+#[test]
+fn config_test_out_of_range_numbers_are_rejected() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+        ("ENV_SFN_MAX_ATTEMPTS", "4294967297"),
+    ]);
+    assert_eq!(
+        _validation_errors(Config::from_sources(None, &env_vars, &build_sdk_config())),
+        vec![String::from(
+            "Env var ENV_SFN_MAX_ATTEMPTS must be a positive integer that fits in u32."
+        )]
+    );
+}
+
+struct _AlwaysReadyRule;
+
+impl TriggerRule for _AlwaysReadyRule {
+    fn describe(&self) -> String {
+        String::from("always ready")
+    }
+
+    fn is_satisfied(
+        &self,
+        _last_success_time: &OffsetDateTime,
+        _dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        Some(true)
+    }
+}
+
+#[test]
+fn config_test_custom_trigger_rules() {
+    let env_vars = _build_env_vars(&[
+        ("ENV_ENDPOINT_URL", "https://example.com/"),
+        ("ENV_AWS_REGION", "eu-west-1"),
+    ]);
+    let config = Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap();
+    let rule_name = format!("ALWAYS{}", rand::random::<u32>());
+    let mut trigger_rules = TriggerRuleRegistry::with_builtins();
+    trigger_rules.register(&rule_name, |_| Ok(Box::new(_AlwaysReadyRule)));
+    let config = config.with_trigger_rules(trigger_rules);
+    let dependencies = vec![String::from("https://example.com/v1/source1")];
+    let parameters = TriggerRuleParameters {
+        pipeline_id: "pipeline1",
+        dependencies: &dependencies,
+        quorum_threshold: None,
+        dependency_expression: None,
+        rule_parameters: None,
+    };
+    let trigger_rule = config.trigger_rules.build(&rule_name, &parameters).unwrap();
+    assert_eq!(trigger_rule.describe(), "always ready");
+    assert_eq!(
+        trigger_rule.is_satisfied(&OffsetDateTime::now_utc(), &[None]),
+        Some(true)
+    );
+    assert!(config.trigger_rules.build("STRICT", &parameters).is_ok());
+}
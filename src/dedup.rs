@@ -0,0 +1,133 @@
+pub mod remote;
+#[cfg(test)]
+mod test_dedup;
+
+use crate::config::{Clients, Config, DedupStoreKind};
+use crate::response::HandlerResponse;
+use crate::routing::Backend;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub enum DedupError {
+    StoreReadError(String),
+    StoreWriteError(String),
+    StoreConfigurationError(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProcessingOutcome {
+    Succeeded {
+        // NOTE: the response of the original delivery, returned again on
+        // replay. Outcomes recorded before it was stored have none.
+        #[serde(default)]
+        response: Option<Box<HandlerResponse>>,
+    },
+    Failed {
+        reason: String,
+        // NOTE: true once the event was persisted to the REST API, so a
+        // replay does not persist the same event a second time.
+        #[serde(default)]
+        is_persisted: bool,
+    },
+}
+
+pub enum DedupStore {
+    InMemory(Mutex<HashMap<String, ProcessingOutcome>>),
+    LocalFile(PathBuf),
+    // NOTE: outcomes are stored with the backend the event is routed to,
+    // so each tenant's API only holds its own processed events.
+    RemoteApi { clients: Clients },
+}
+
+impl DedupStore {
+    pub fn from_config(config: &Config) -> DedupStore {
+        match &config.dedup_store {
+            DedupStoreKind::Memory => DedupStore::InMemory(Mutex::new(HashMap::new())),
+            DedupStoreKind::File(path) => DedupStore::LocalFile(path.clone()),
+            DedupStoreKind::Api => DedupStore::RemoteApi {
+                clients: config.clients.clone(),
+            },
+        }
+    }
+
+    pub async fn fetch_outcome(
+        &self,
+        event_id: &str,
+        backend: &Backend,
+    ) -> Result<Option<ProcessingOutcome>, DedupError> {
+        match self {
+            DedupStore::InMemory(outcomes) => {
+                let outcomes = outcomes.lock().map_err(|e| {
+                    DedupError::StoreReadError(format!("In-memory store is poisoned:\n{:?}", e))
+                })?;
+                Ok(outcomes.get(event_id).cloned())
+            }
+            DedupStore::LocalFile(path) => Ok(_read_outcomes_file(path)?.remove(event_id)),
+            DedupStore::RemoteApi { clients } => {
+                remote::fetch_outcome(event_id, backend, clients).await
+            }
+        }
+    }
+
+    pub async fn record_outcome(
+        &self,
+        event_id: &str,
+        outcome: &ProcessingOutcome,
+        backend: &Backend,
+    ) -> Result<(), DedupError> {
+        match self {
+            DedupStore::InMemory(outcomes) => {
+                let mut outcomes = outcomes.lock().map_err(|e| {
+                    DedupError::StoreWriteError(format!("In-memory store is poisoned:\n{:?}", e))
+                })?;
+                outcomes.insert(String::from(event_id), outcome.clone());
+                Ok(())
+            }
+            DedupStore::LocalFile(path) => {
+                let mut outcomes = _read_outcomes_file(path)?;
+                outcomes.insert(String::from(event_id), outcome.clone());
+                _write_outcomes_file(path, &outcomes)
+            }
+            DedupStore::RemoteApi { clients } => {
+                remote::record_outcome(event_id, outcome, backend, clients).await
+            }
+        }
+    }
+}
+
+fn _read_outcomes_file(path: &Path) -> Result<HashMap<String, ProcessingOutcome>, DedupError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        DedupError::StoreReadError(format!(
+            "Failed to read dedup store file {:?}:\n{:?}",
+            path, e
+        ))
+    })?;
+    serde_json::from_str(&contents).map_err(|e| {
+        DedupError::StoreReadError(format!(
+            "Failed to deserialize dedup store file {:?}:\n{:?}",
+            path, e
+        ))
+    })
+}
+
+fn _write_outcomes_file(
+    path: &Path,
+    outcomes: &HashMap<String, ProcessingOutcome>,
+) -> Result<(), DedupError> {
+    let contents = serde_json::to_string(outcomes).map_err(|e| {
+        DedupError::StoreWriteError(format!("Failed to serialize dedup store:\n{:?}", e))
+    })?;
+    std::fs::write(path, contents).map_err(|e| {
+        DedupError::StoreWriteError(format!(
+            "Failed to write dedup store file {:?}:\n{:?}",
+            path, e
+        ))
+    })
+}
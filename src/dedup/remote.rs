@@ -0,0 +1,89 @@
+use super::{DedupError, ProcessingOutcome};
+use crate::config::Clients;
+use crate::pipeline_effects::endpoint::{
+    construct_endpoint_url, fetch_optional_rest_model, write_rest_model,
+};
+use crate::routing::Backend;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ProcessedEventRestModel {
+    event_id: String,
+    outcome: ProcessingOutcome,
+}
+
+pub async fn fetch_outcome(
+    event_id: &str,
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<Option<ProcessingOutcome>, DedupError> {
+    let endpoint_url = _get_processed_event_url(event_id, &backend.endpoint_prefix)?;
+    fetch_optional_rest_model::<ProcessedEventRestModel, String>(
+        endpoint_url.as_str(),
+        backend,
+        clients,
+        "".to_string(),
+        &HashMap::new(),
+        "GET",
+    )
+    .await
+    .map(|maybe_model| maybe_model.map(|model| model.outcome))
+    .map_err(|e| {
+        DedupError::StoreReadError(format!(
+            "Failed to fetch processed event {} from remote api:\n{:?}",
+            event_id, e
+        ))
+    })
+}
+
+pub async fn record_outcome(
+    event_id: &str,
+    outcome: &ProcessingOutcome,
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<(), DedupError> {
+    let endpoint_url = _get_processed_event_url(event_id, &backend.endpoint_prefix)?;
+    let body = serde_json::to_string(&ProcessedEventRestModel {
+        event_id: String::from(event_id),
+        outcome: outcome.clone(),
+    })
+    .map_err(|e| {
+        DedupError::StoreWriteError(format!(
+            "Failed to serialize processed event {}:\n{:?}",
+            event_id, e
+        ))
+    })?;
+    let headers = HashMap::from([(
+        String::from("Content-Type"),
+        String::from("application/json"),
+    )]);
+    write_rest_model(
+        endpoint_url.as_str(),
+        backend,
+        clients,
+        body,
+        &headers,
+        "PUT",
+    )
+    .await
+    .map_err(|e| {
+        DedupError::StoreWriteError(format!(
+            "Failed to record processed event {} to remote api:\n{:?}",
+            event_id, e
+        ))
+    })
+}
+
+fn _get_processed_event_url(event_id: &str, endpoint_prefix: &str) -> Result<String, DedupError> {
+    construct_endpoint_url(
+        endpoint_prefix,
+        &vec![String::from("processed-events"), String::from(event_id)],
+    )
+    .map_err(|e| {
+        DedupError::StoreConfigurationError(format!(
+            "Failed to construct processed events endpoint url:\n{:?}",
+            e
+        ))
+    })
+}
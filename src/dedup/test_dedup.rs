@@ -0,0 +1,138 @@
+use super::*;
+
+fn _build_backend() -> Backend {
+    Backend {
+        endpoint_prefix: String::from("http://127.0.0.1:9/"),
+        aws_region: String::from("eu-west-1"),
+        credentials_profile: None,
+        role_arn: None,
+    }
+}
+
+#[tokio::test]
+async fn dedup_store_test_in_memory_round_trip() {
+    let store = DedupStore::InMemory(Mutex::new(HashMap::new()));
+    let event_id = format!("event{}", rand::random::<u32>());
+    assert_eq!(
+        store
+            .fetch_outcome(&event_id, &_build_backend())
+            .await
+            .unwrap(),
+        None
+    );
+    store
+        .record_outcome(
+            &event_id,
+            &ProcessingOutcome::Succeeded { response: None },
+            &_build_backend(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .fetch_outcome(&event_id, &_build_backend())
+            .await
+            .unwrap(),
+        Some(ProcessingOutcome::Succeeded { response: None })
+    );
+}
+
+#[tokio::test]
+async fn dedup_store_test_in_memory_overwrites_failed_outcome() {
+    let store = DedupStore::InMemory(Mutex::new(HashMap::new()));
+    let event_id = format!("event{}", rand::random::<u32>());
+    let failure = ProcessingOutcome::Failed {
+        reason: format!("reason{}", rand::random::<u32>()),
+        is_persisted: false,
+    };
+    store
+        .record_outcome(&event_id, &failure, &_build_backend())
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .fetch_outcome(&event_id, &_build_backend())
+            .await
+            .unwrap(),
+        Some(failure)
+    );
+    store
+        .record_outcome(
+            &event_id,
+            &ProcessingOutcome::Succeeded { response: None },
+            &_build_backend(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .fetch_outcome(&event_id, &_build_backend())
+            .await
+            .unwrap(),
+        Some(ProcessingOutcome::Succeeded { response: None })
+    );
+}
+
+#[tokio::test]
+async fn dedup_store_test_local_file_round_trip() {
+    let path = std::env::temp_dir().join(format!("conductor-dedup-{}.json", rand::random::<u32>()));
+    let store = DedupStore::LocalFile(path.clone());
+    let first_event_id = format!("event{}", rand::random::<u32>());
+    let second_event_id = format!("event{}", rand::random::<u32>());
+    let failure = ProcessingOutcome::Failed {
+        reason: String::from("boom"),
+        is_persisted: true,
+    };
+    assert_eq!(
+        store
+            .fetch_outcome(&first_event_id, &_build_backend())
+            .await
+            .unwrap(),
+        None
+    );
+    store
+        .record_outcome(
+            &first_event_id,
+            &ProcessingOutcome::Succeeded { response: None },
+            &_build_backend(),
+        )
+        .await
+        .unwrap();
+    store
+        .record_outcome(&second_event_id, &failure, &_build_backend())
+        .await
+        .unwrap();
+    // a fresh store pointed at the same file sees previously recorded outcomes
+    let reopened_store = DedupStore::LocalFile(path.clone());
+    assert_eq!(
+        reopened_store
+            .fetch_outcome(&first_event_id, &_build_backend())
+            .await
+            .unwrap(),
+        Some(ProcessingOutcome::Succeeded { response: None })
+    );
+    assert_eq!(
+        reopened_store
+            .fetch_outcome(&second_event_id, &_build_backend())
+            .await
+            .unwrap(),
+        Some(failure)
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn dedup_store_test_local_file_corrupt_contents() {
+    let path = std::env::temp_dir().join(format!("conductor-dedup-{}.json", rand::random::<u32>()));
+    std::fs::write(&path, "not json").unwrap();
+    let store = DedupStore::LocalFile(path.clone());
+    let result = store.fetch_outcome("event", &_build_backend()).await;
+    std::fs::remove_file(path).unwrap();
+    assert!(matches!(result, Err(DedupError::StoreReadError(_))));
+}
+
+#[test]
+fn processing_outcome_test_succeeded_without_response() {
+    let outcome: ProcessingOutcome = serde_json::from_str(r#"{"status": "succeeded"}"#).unwrap();
+    assert_eq!(outcome, ProcessingOutcome::Succeeded { response: None });
+}
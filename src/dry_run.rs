@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod test_dry_run;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "effect", rename_all = "snake_case")]
+pub enum PlannedEffect {
+    PersistEvent {
+        event_id: String,
+        path: String,
+    },
+    SendTaskHeartbeat,
+    SendTaskSuccess,
+    StartExecution {
+        state_machine_arn: String,
+        input: Option<String>,
+    },
+    MarkEventAsBlocking {
+        event_id: String,
+    },
+    MarkPermitAsExpired,
+    SendTaskFailure {
+        error: String,
+        cause: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EffectPlan {
+    pub event_id: String,
+    pub pipelines: BTreeMap<String, Vec<PlannedEffect>>,
+}
+
+#[derive(Default)]
+pub struct EffectRecorder {
+    effects: Mutex<Vec<(String, PlannedEffect)>>,
+}
+
+impl EffectRecorder {
+    pub fn new() -> EffectRecorder {
+        EffectRecorder {
+            effects: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn record(&self, owner_id: &str, effect: PlannedEffect) {
+        // NOTE: a poisoned lock only means another recording panicked,
+        // the recorded effects themselves are still consistent.
+        let mut effects = match self.effects.lock() {
+            Ok(effects) => effects,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        effects.push((String::from(owner_id), effect));
+    }
+
+    pub fn into_plan(self, event_id: &str) -> EffectPlan {
+        let effects = match self.effects.into_inner() {
+            Ok(effects) => effects,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut pipelines: BTreeMap<String, Vec<PlannedEffect>> = BTreeMap::new();
+        for (owner_id, effect) in effects {
+            pipelines.entry(owner_id).or_default().push(effect);
+        }
+        EffectPlan {
+            event_id: String::from(event_id),
+            pipelines,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use super::*;
+use serde_json::json;
+
+#[test]
+fn effect_recorder_test_groups_effects_per_pipeline() {
+    let recorder = EffectRecorder::new();
+    let event_id = format!("event{}", rand::random::<u32>());
+    recorder.record("pipeline1", PlannedEffect::SendTaskHeartbeat);
+    recorder.record(
+        "pipeline2",
+        PlannedEffect::MarkEventAsBlocking {
+            event_id: String::from(&event_id),
+        },
+    );
+    recorder.record("pipeline1", PlannedEffect::SendTaskSuccess);
+    let plan = recorder.into_plan(&event_id);
+    assert_eq!(plan.event_id, event_id);
+    assert_eq!(
+        plan.pipelines.get("pipeline1"),
+        Some(&vec![
+            PlannedEffect::SendTaskHeartbeat,
+            PlannedEffect::SendTaskSuccess
+        ])
+    );
+    assert_eq!(plan.pipelines.get("pipeline2").map(Vec::len), Some(1));
+}
+
+#[test]
+fn effect_plan_test_serialization() {
+    let recorder = EffectRecorder::new();
+    recorder.record(
+        "pipeline1",
+        PlannedEffect::StartExecution {
+            state_machine_arn: String::from("arn:aws:states:eu-west-1:1:stateMachine:pipeline1"),
+            input: None,
+        },
+    );
+    recorder.record("pipeline1", PlannedEffect::SendTaskSuccess);
+    let plan = recorder.into_plan("event1");
+    assert_eq!(
+        serde_json::to_value(&plan).unwrap(),
+        json!({
+            "event_id": "event1",
+            "pipelines": {
+                "pipeline1": [
+                    {
+                        "effect": "start_execution",
+                        "state_machine_arn": "arn:aws:states:eu-west-1:1:stateMachine:pipeline1",
+                        "input": null
+                    },
+                    {"effect": "send_task_success"}
+                ]
+            }
+        })
+    );
+}
@@ -9,10 +9,30 @@ pub enum EventPayload {
         success_time: OffsetDateTime,
         callback_token: String,
     },
+    DataPipelineFailure {
+        id: String,
+        failure: PipelineFailure,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineFailureStatus {
+    Failed,
+    TimedOut,
+    Aborted,
+}
+
+pub struct PipelineFailure {
+    pub status: PipelineFailureStatus,
+    pub error: Option<String>,
+    pub cause: Option<String>,
+    pub state_machine_arn: Option<String>,
+    pub execution_input: Option<String>,
 }
 
 pub struct Event {
     pub id: String,
+    pub source: String,
     pub event_time: OffsetDateTime,
     pub payload: EventPayload,
 }
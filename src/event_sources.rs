@@ -0,0 +1,5 @@
+pub mod eventbridge;
+pub mod s3;
+pub mod schedule;
+pub mod sqs;
+pub mod step_functions;
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod test_eventbridge;
+
+use super::step_functions::{StepFunctionsStatusDetail, STEP_FUNCTIONS_STATUS_DETAIL_TYPE};
+use crate::events::versions::LEGACY_SCHEMA_VERSION;
+use crate::{Request, RequestDetail, RequestFailure, RequestPayload, RequestType};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+pub const DATA_SOURCE_DETAIL_TYPE: &str = "Data Source Event";
+pub const PIPELINE_DETAIL_TYPE: &str = "Pipeline Event";
+
+#[derive(Deserialize, JsonSchema, Debug, Clone)]
+#[schemars(rename = "Request")]
+pub struct RawRequest {
+    source: String,
+    event_id: Option<String>,
+    id: Option<String>,
+    #[serde(rename = "detail-type")]
+    detail_type: Option<String>,
+    time: Option<String>,
+    account: Option<String>,
+    region: Option<String>,
+    #[serde(default)]
+    resources: Vec<String>,
+    #[schemars(with = "RawRequestDetail")]
+    detail: serde_json::Value,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone)]
+#[schemars(rename = "RequestDetail")]
+pub struct RawRequestDetail {
+    pub(super) schema_version: Option<u32>,
+    pub(super) creator_type: Option<RequestType>,
+    pub(super) creator_id: String,
+    pub(super) payload: RawRequestPayload,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone)]
+#[schemars(rename = "RequestPayload")]
+pub struct RawRequestPayload {
+    pub(super) callback_token: Option<String>,
+    pub(super) success_time: Option<String>,
+    pub(super) event_time: Option<String>,
+    pub(super) failure: Option<RequestFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBridgeMetadata {
+    pub detail_type: String,
+    pub time: Option<String>,
+    pub account: Option<String>,
+    pub region: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl TryFrom<RawRequest> for Request {
+    type Error = String;
+
+    fn try_from(raw: RawRequest) -> Result<Self, Self::Error> {
+        let mut ignored_reason = None;
+        let detail = match raw.detail_type.as_deref() {
+            Some(STEP_FUNCTIONS_STATUS_DETAIL_TYPE) => {
+                let status_detail = serde_json::from_value::<StepFunctionsStatusDetail>(raw.detail)
+                    .map_err(|e| format!("Malformed Step Functions status change detail: {}", e))?;
+                ignored_reason = status_detail.ignored_reason();
+                status_detail.into_raw_request_detail()?
+            }
+            _ => serde_json::from_value::<RawRequestDetail>(raw.detail)
+                .map_err(|e| format!("Malformed request detail: {}", e))?,
+        };
+        let creator_type = _resolve_creator_type(&detail.creator_type, &raw.detail_type)?;
+        let event_id = raw.event_id.or(raw.id).ok_or(String::from(
+            "Request has neither an event_id nor an EventBridge id.",
+        ))?;
+        let event_time = detail
+            .payload
+            .event_time
+            .or(raw.time.clone())
+            .ok_or(String::from(
+                "Request has neither a detail.payload.event_time nor an EventBridge time.",
+            ))?;
+        let envelope = raw.detail_type.map(|detail_type| EventBridgeMetadata {
+            detail_type,
+            time: raw.time,
+            account: raw.account,
+            region: raw.region,
+            resources: raw.resources,
+        });
+        Ok(Request {
+            source: raw.source,
+            event_id,
+            envelope,
+            ignored_reason,
+            detail: RequestDetail {
+                schema_version: detail.schema_version.unwrap_or(LEGACY_SCHEMA_VERSION),
+                creator_type,
+                creator_id: detail.creator_id,
+                payload: RequestPayload {
+                    callback_token: detail.payload.callback_token,
+                    success_time: detail.payload.success_time,
+                    event_time,
+                    failure: detail.payload.failure,
+                },
+            },
+        })
+    }
+}
+
+fn _resolve_creator_type(
+    maybe_creator_type: &Option<RequestType>,
+    maybe_detail_type: &Option<String>,
+) -> Result<RequestType, String> {
+    // NOTE: producers may pick their own detail-type for the custom shape,
+    // so an unrecognized detail-type defers to detail.creator_type.
+    let maybe_routed_type = maybe_detail_type.as_deref().and_then(_route_detail_type);
+    match (maybe_creator_type, maybe_routed_type) {
+        (Some(creator_type), None) => Ok(creator_type.clone()),
+        (None, Some(routed_type)) => Ok(routed_type),
+        (Some(creator_type), Some(routed_type)) => {
+            if creator_type.eq(&routed_type) {
+                Ok(routed_type)
+            } else {
+                Err(format!(
+                    "Conflict between detail.creator_type {:?} and detail-type {:?}.",
+                    creator_type, maybe_detail_type
+                ))
+            }
+        }
+        (None, None) => match maybe_detail_type {
+            Some(detail_type) => Err(format!(
+                "Unrecognized EventBridge detail-type {} and no detail.creator_type.",
+                detail_type
+            )),
+            None => Err(String::from(
+                "Request has neither a detail.creator_type nor an EventBridge detail-type.",
+            )),
+        },
+    }
+}
+
+fn _route_detail_type(detail_type: &str) -> Option<RequestType> {
+    match detail_type {
+        DATA_SOURCE_DETAIL_TYPE => Some(RequestType::DataSource),
+        PIPELINE_DETAIL_TYPE => Some(RequestType::Pipeline),
+        STEP_FUNCTIONS_STATUS_DETAIL_TYPE => Some(RequestType::PipelineFailure),
+        _ => None,
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{Request, RequestType};
+use serde_json::json;
+
+#[test]
+fn request_deserialization_test_custom_shape() {
+    let rand_num_event_id = rand::random::<u32>();
+    let request = serde_json::from_value::<Request>(json!({
+        "source": "conductor.test",
+        "event_id": format!("event{}", rand_num_event_id),
+        "detail": {
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {
+                "event_time": "2023-09-21T10:05:00Z"
+            }
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.event_id, format!("event{}", rand_num_event_id));
+    assert_eq!(request.detail.creator_type, RequestType::DataSource);
+    assert_eq!(request.detail.payload.event_time, "2023-09-21T10:05:00Z");
+    assert_eq!(request.detail.schema_version, 1);
+    assert!(request.envelope.is_none());
+}
+
+#[test]
+fn request_deserialization_test_explicit_schema_version() {
+    let request = serde_json::from_value::<Request>(json!({
+        "source": "conductor.test",
+        "event_id": "event1",
+        "detail": {
+            "schema_version": 2,
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {
+                "event_time": "2023-09-21T10:05:00Z"
+            }
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.detail.schema_version, 2);
+}
+
+#[test]
+fn request_deserialization_test_eventbridge_envelope_fills_missing_fields() {
+    let rand_num_event_id = rand::random::<u32>();
+    let request = serde_json::from_value::<Request>(json!({
+        "version": "0",
+        "id": format!("eb{}", rand_num_event_id),
+        "detail-type": "Pipeline Event",
+        "source": "com.hotpotato.pipelines",
+        "account": "123456789012",
+        "time": "2023-09-21T10:05:00Z",
+        "region": "us-east-1",
+        "resources": ["arn:aws:states:us-east-1:123456789012:stateMachine:pipeline1"],
+        "detail": {
+            "creator_id": "pipeline1",
+            "payload": {
+                "callback_token": "token",
+                "success_time": "2023-09-21T10:00:00Z"
+            }
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.event_id, format!("eb{}", rand_num_event_id));
+    assert_eq!(request.detail.creator_type, RequestType::Pipeline);
+    assert_eq!(request.detail.payload.event_time, "2023-09-21T10:05:00Z");
+    let envelope = request.envelope.unwrap();
+    assert_eq!(envelope.detail_type, "Pipeline Event");
+    assert_eq!(envelope.account, Some(String::from("123456789012")));
+    assert_eq!(envelope.region, Some(String::from("us-east-1")));
+    assert_eq!(envelope.resources.len(), 1);
+}
+
+#[test]
+fn request_deserialization_test_custom_fields_take_precedence() {
+    let request = serde_json::from_value::<Request>(json!({
+        "id": "eb-id",
+        "event_id": "custom-id",
+        "detail-type": "Data Source Event",
+        "source": "com.hotpotato.sources",
+        "time": "2023-09-21T10:05:00Z",
+        "detail": {
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {
+                "event_time": "2023-09-21T09:00:00Z"
+            }
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.event_id, "custom-id");
+    assert_eq!(request.detail.payload.event_time, "2023-09-21T09:00:00Z");
+}
+
+#[test]
+fn request_deserialization_test_conflicting_detail_type() {
+    let result = serde_json::from_value::<Request>(json!({
+        "id": "eb-id",
+        "detail-type": "Pipeline Event",
+        "source": "com.hotpotato.sources",
+        "time": "2023-09-21T10:05:00Z",
+        "detail": {
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {}
+        }
+    }));
+    assert!(result.unwrap_err().to_string().contains("Conflict"));
+}
+
+#[test]
+fn request_deserialization_test_unrecognized_detail_type() {
+    let result = serde_json::from_value::<Request>(json!({
+        "id": "eb-id",
+        "detail-type": "Something Else",
+        "source": "com.hotpotato.sources",
+        "time": "2023-09-21T10:05:00Z",
+        "detail": {
+            "creator_id": "source1",
+            "payload": {}
+        }
+    }));
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Unrecognized EventBridge detail-type"));
+}
+
+#[test]
+fn request_deserialization_test_unrecognized_detail_type_with_creator_type() {
+    let request = serde_json::from_value::<Request>(json!({
+        "id": "eb-id",
+        "detail-type": "Something Else",
+        "source": "com.hotpotato.sources",
+        "time": "2023-09-21T10:05:00Z",
+        "detail": {
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {}
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.detail.creator_type, RequestType::DataSource);
+    assert_eq!(request.envelope.unwrap().detail_type, "Something Else");
+}
+
+#[test]
+fn request_deserialization_test_missing_event_time() {
+    let result = serde_json::from_value::<Request>(json!({
+        "source": "conductor.test",
+        "event_id": "event1",
+        "detail": {
+            "creator_type": "DataSource",
+            "creator_id": "source1",
+            "payload": {}
+        }
+    }));
+    assert!(result.unwrap_err().to_string().contains("event_time"));
+}
+
+#[test]
+fn request_deserialization_test_step_functions_execution_failure() {
+    let request = serde_json::from_value::<Request>(json!({
+        "version": "0",
+        "id": "eb-id",
+        "detail-type": "Step Functions Execution Status Change",
+        "source": "aws.states",
+        "account": "123456789012",
+        "time": "2023-09-21T10:05:00Z",
+        "region": "us-east-1",
+        "resources": ["arn:aws:states:us-east-1:123456789012:execution:pipeline1:run1"],
+        "detail": {
+            "executionArn": "arn:aws:states:us-east-1:123456789012:execution:pipeline1:run1",
+            "stateMachineArn": "arn:aws:states:us-east-1:123456789012:stateMachine:pipeline1",
+            "name": "run1",
+            "status": "TIMED_OUT",
+            "startDate": 1695290000000i64,
+            "stopDate": 1695290700000i64,
+            "input": "{}",
+            "error": null,
+            "cause": null
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.event_id, "eb-id");
+    assert_eq!(request.ignored_reason, None);
+    assert_eq!(request.detail.creator_type, RequestType::PipelineFailure);
+    assert_eq!(request.detail.creator_id, "pipeline1");
+    assert_eq!(
+        request.detail.payload.event_time,
+        "2023-09-21T10:05:00.000000000Z"
+    );
+    let failure = request.detail.payload.failure.unwrap();
+    assert_eq!(failure.status, "TIMED_OUT");
+    assert_eq!(
+        failure.state_machine_arn,
+        Some(String::from(
+            "arn:aws:states:us-east-1:123456789012:stateMachine:pipeline1"
+        ))
+    );
+    assert_eq!(failure.execution_input, Some(String::from("{}")));
+}
+
+#[test]
+fn request_deserialization_test_step_functions_success_is_ignored() {
+    let request = serde_json::from_value::<Request>(json!({
+        "id": "eb-id",
+        "detail-type": "Step Functions Execution Status Change",
+        "source": "aws.states",
+        "time": "2023-09-21T10:05:00Z",
+        "detail": {
+            "executionArn": "arn:aws:states:us-east-1:123456789012:execution:pipeline1:run1",
+            "stateMachineArn": "arn:aws:states:us-east-1:123456789012:stateMachine:pipeline1",
+            "status": "SUCCEEDED"
+        }
+    }))
+    .unwrap();
+    assert_eq!(request.detail.creator_id, "pipeline1");
+    assert!(request.detail.payload.failure.is_none());
+    assert!(request.ignored_reason.unwrap().contains("SUCCEEDED"));
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod test_s3;
+
+use crate::config::Config;
+use crate::dedup::DedupStore;
+use crate::events::versions::CURRENT_SCHEMA_VERSION;
+use crate::{handler, Request, RequestDetail, RequestPayload, RequestType};
+use lambda_runtime::{Error, LambdaEvent};
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use simple_error::simple_error;
+use std::future::Future;
+use tracing::{event, Level};
+
+pub const S3_REQUEST_SOURCE: &str = "aws.s3";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct S3Event {
+    #[serde(rename = "Records")]
+    records: Vec<S3EventRecord>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct S3EventRecord {
+    #[serde(rename = "eventName")]
+    event_name: String,
+    #[serde(rename = "eventTime")]
+    event_time: String,
+    s3: S3Entity,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct S3Entity {
+    bucket: S3Bucket,
+    object: S3Object,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct S3Bucket {
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct S3Object {
+    key: String,
+    sequencer: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct S3SourceMappingRule {
+    bucket: String,
+    #[serde(default)]
+    prefix: String,
+    data_source_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct S3SourceMapping {
+    rules: Vec<S3SourceMappingRule>,
+}
+
+impl S3SourceMapping {
+    pub fn from_rules(rules: Vec<S3SourceMappingRule>) -> S3SourceMapping {
+        S3SourceMapping { rules }
+    }
+
+    pub fn resolve_data_source_id(&self, bucket: &str, key: &str) -> Option<String> {
+        // the most specific (longest) matching prefix wins
+        self.rules
+            .iter()
+            .filter(|rule| rule.bucket.eq(bucket) && key.starts_with(&rule.prefix))
+            .max_by_key(|rule| rule.prefix.len())
+            .map(|rule| String::from(&rule.data_source_id))
+    }
+}
+
+fn _decode_object_key(key: &str) -> String {
+    let key = key.replace('+', " ");
+    percent_decode_str(&key).decode_utf8_lossy().to_string()
+}
+
+fn _map_record_to_request(record: S3EventRecord, mapping: &S3SourceMapping) -> Option<Request> {
+    if !record.event_name.starts_with("ObjectCreated:") {
+        return None;
+    }
+    let bucket = record.s3.bucket.name;
+    let key = _decode_object_key(&record.s3.object.key);
+    let data_source_id = mapping.resolve_data_source_id(&bucket, &key)?;
+    let event_id = format!(
+        "s3:{}/{}:{}",
+        &bucket,
+        &key,
+        record.s3.object.sequencer.unwrap_or_default()
+    );
+    Some(Request {
+        source: String::from(S3_REQUEST_SOURCE),
+        event_id,
+        envelope: None,
+        ignored_reason: None,
+        detail: RequestDetail {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            creator_type: RequestType::DataSource,
+            creator_id: data_source_id,
+            payload: RequestPayload {
+                callback_token: None,
+                success_time: None,
+                event_time: record.event_time,
+                failure: None,
+            },
+        },
+    })
+}
+
+async fn process_s3_records<F, FutF>(
+    event: LambdaEvent<S3Event>,
+    mapping: &S3SourceMapping,
+    request_handling_fn: F,
+) -> Result<(), Error>
+where
+    F: Fn(LambdaEvent<Request>) -> FutF,
+    FutF: Future<Output = Result<(), Error>>,
+{
+    let mut error_strings = vec![];
+    for record in event.payload.records {
+        let object_location = format!("s3://{}/{}", &record.s3.bucket.name, &record.s3.object.key);
+        match _map_record_to_request(record, mapping) {
+            Some(request) => {
+                let request_event = LambdaEvent::new(request, event.context.clone());
+                if let Err(error) = request_handling_fn(request_event).await {
+                    error_strings.push(format!("{}: {}", object_location, error));
+                }
+            }
+            None => {
+                let mssg = format!(
+                    "S3 notification for {} is not an object creation in a mapped data source; skipping.",
+                    object_location
+                );
+                event!(Level::INFO, mssg);
+            }
+        }
+    }
+    if !error_strings.is_empty() {
+        Err(Box::new(simple_error!(format!(
+            "Not all S3 notifications were successfully processed.\n{:?}",
+            error_strings
+        ))))
+    } else {
+        Ok(())
+    }
+}
+
+pub async fn s3_handler(
+    event: LambdaEvent<S3Event>,
+    config: &Config,
+    mapping: &S3SourceMapping,
+    dedup_store: &DedupStore,
+) -> Result<(), Error> {
+    let request_handling_fn = |request_event| async move {
+        handler(request_event, config, dedup_store)
+            .await
+            .map(|_| ())
+    };
+    process_s3_records(event, mapping, request_handling_fn).await
+}
@@ -0,0 +1,122 @@
+use super::*;
+use lambda_runtime::Context;
+use serde_json::json;
+use std::sync::Mutex;
+
+fn _build_mapping() -> S3SourceMapping {
+    S3SourceMapping::from_rules(
+        serde_json::from_value(json!([
+            {"bucket": "landing", "prefix": "raw/", "data_source_id": "raw"},
+            {"bucket": "landing", "prefix": "raw/orders/", "data_source_id": "orders"},
+            {"bucket": "snapshots", "data_source_id": "full_snapshot"}
+        ]))
+        .unwrap(),
+    )
+}
+
+fn _build_s3_event(records: Vec<(&str, &str, &str)>) -> LambdaEvent<S3Event> {
+    let records: Vec<serde_json::Value> = records
+        .iter()
+        .map(|(event_name, bucket, key)| {
+            json!({
+                "eventVersion": "2.1",
+                "eventSource": "aws:s3",
+                "eventTime": "2023-09-21T10:05:00.000Z",
+                "eventName": event_name,
+                "s3": {
+                    "bucket": {"name": bucket},
+                    "object": {"key": key, "sequencer": "0055AED6DCD90281E5"}
+                }
+            })
+        })
+        .collect();
+    let s3_event = serde_json::from_value::<S3Event>(json!({ "Records": records })).unwrap();
+    LambdaEvent::new(s3_event, Context::default())
+}
+
+#[test]
+fn resolve_data_source_id_test_longest_prefix_wins() {
+    let mapping = _build_mapping();
+    assert_eq!(
+        mapping.resolve_data_source_id("landing", "raw/orders/2023/09/21.csv"),
+        Some(String::from("orders"))
+    );
+    assert_eq!(
+        mapping.resolve_data_source_id("landing", "raw/customers/2023/09/21.csv"),
+        Some(String::from("raw"))
+    );
+    assert_eq!(
+        mapping.resolve_data_source_id("snapshots", &format!("{}.parquet", rand::random::<u32>())),
+        Some(String::from("full_snapshot"))
+    );
+    assert_eq!(
+        mapping.resolve_data_source_id("landing", "curated/orders.csv"),
+        None
+    );
+    assert_eq!(
+        mapping.resolve_data_source_id("elsewhere", "raw/orders/x"),
+        None
+    );
+}
+
+#[tokio::test]
+async fn process_s3_records_test_maps_created_objects_to_data_sources() {
+    let mapping = _build_mapping();
+    let s3_event = _build_s3_event(vec![
+        ("ObjectCreated:Put", "landing", "raw/orders/day+1%3D.csv"),
+        ("ObjectRemoved:Delete", "landing", "raw/orders/old.csv"),
+        ("ObjectCreated:Put", "landing", "unmapped/file.csv"),
+        (
+            "ObjectCreated:CompleteMultipartUpload",
+            "snapshots",
+            "full.parquet",
+        ),
+    ]);
+    let handled_requests = Mutex::new(vec![]);
+    let request_handling_fn = |request_event: LambdaEvent<Request>| {
+        handled_requests.lock().unwrap().push(request_event.payload);
+        async move { Ok(()) }
+    };
+    let result = process_s3_records(s3_event, &mapping, request_handling_fn).await;
+    assert!(result.is_ok());
+    let handled_requests = handled_requests.into_inner().unwrap();
+    assert_eq!(handled_requests.len(), 2);
+    assert_eq!(handled_requests[0].source, S3_REQUEST_SOURCE);
+    assert_eq!(
+        handled_requests[0].detail.creator_type,
+        RequestType::DataSource
+    );
+    assert_eq!(handled_requests[0].detail.creator_id, "orders");
+    assert_eq!(
+        handled_requests[0].event_id,
+        "s3:landing/raw/orders/day 1=.csv:0055AED6DCD90281E5"
+    );
+    assert_eq!(
+        handled_requests[0].detail.payload.event_time,
+        "2023-09-21T10:05:00.000Z"
+    );
+    assert_eq!(handled_requests[1].detail.creator_id, "full_snapshot");
+}
+
+#[tokio::test]
+async fn process_s3_records_test_reports_failures() {
+    let mapping = _build_mapping();
+    let s3_event = _build_s3_event(vec![
+        ("ObjectCreated:Put", "landing", "raw/orders/a.csv"),
+        ("ObjectCreated:Put", "landing", "raw/b.csv"),
+    ]);
+    let request_handling_fn = |request_event: LambdaEvent<Request>| {
+        let is_failing = request_event.payload.detail.creator_id == "raw";
+        async move {
+            if is_failing {
+                Err(Error::from(simple_error!("pipeline failed to trigger")))
+            } else {
+                Ok(())
+            }
+        }
+    };
+    let result = process_s3_records(s3_event, &mapping, request_handling_fn).await;
+    let error_string = result.unwrap_err().to_string();
+    assert!(error_string.contains("s3://landing/raw/b.csv"));
+    assert!(!error_string.contains("s3://landing/raw/orders/a.csv"));
+}
@@ -0,0 +1,59 @@
+use crate::config::Config;
+use crate::dry_run::EffectRecorder;
+use crate::pipeline_effects::{evaluation_deadline, sweep_pipelines};
+use crate::response::HandlerResponse;
+use lambda_runtime::{Error, LambdaEvent};
+use serde::Deserialize;
+use simple_error::simple_error;
+use tracing::{event, Level};
+
+pub const SCHEDULED_EVENT_DETAIL_TYPE: &str = "Scheduled Event";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScheduledEvent {
+    id: String,
+    #[serde(rename = "detail-type")]
+    detail_type: String,
+    time: String,
+    #[serde(default)]
+    resources: Vec<String>,
+}
+
+pub async fn schedule_handler(
+    event: LambdaEvent<ScheduledEvent>,
+    config: &Config,
+) -> Result<HandlerResponse, Error> {
+    let deadline = evaluation_deadline(&event.context, config.deadline_margin);
+    let scheduled_event = event.payload;
+    if scheduled_event.detail_type != SCHEDULED_EVENT_DETAIL_TYPE {
+        return Err(Box::new(simple_error!(format!(
+            "Unexpected detail-type {} for a scheduled event; expected {}.",
+            &scheduled_event.detail_type, SCHEDULED_EVENT_DETAIL_TYPE
+        )))
+        .into());
+    }
+    let mssg = format!(
+        "Starting reconciliation sweep {} scheduled at {} by {:?}.",
+        &scheduled_event.id, &scheduled_event.time, &scheduled_event.resources
+    );
+    event!(Level::INFO, mssg);
+    if config.dry_run {
+        let recorder = EffectRecorder::new();
+        let result = sweep_pipelines(config, Some(&recorder), deadline)
+            .await
+            .map_err(Error::from);
+        let plan = recorder.into_plan(&scheduled_event.id);
+        let mssg = format!(
+            "Dry run of reconciliation sweep {} planned effects: {}",
+            &scheduled_event.id,
+            serde_json::to_string(&plan).unwrap_or_default()
+        );
+        event!(Level::INFO, mssg);
+        return HandlerResponse::from_dry_run(&scheduled_event.id, result, plan)?
+            .into_sweep_result();
+    }
+    let result = sweep_pipelines(config, None, deadline)
+        .await
+        .map_err(Error::from);
+    HandlerResponse::from_result(&scheduled_event.id, result)?.into_sweep_result()
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod test_sqs;
+
+use crate::config::Config;
+use crate::dedup::DedupStore;
+use crate::{handler, Request};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use tracing::{event, Level};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SqsEvent {
+    #[serde(rename = "Records")]
+    records: Vec<SqsMessage>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SqsMessage {
+    #[serde(rename = "messageId")]
+    message_id: String,
+    body: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SqsBatchResponse {
+    #[serde(rename = "batchItemFailures")]
+    batch_item_failures: Vec<SqsBatchItemFailure>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SqsBatchItemFailure {
+    #[serde(rename = "itemIdentifier")]
+    item_identifier: String,
+}
+
+async fn process_sqs_batch<F, FutF>(
+    event: LambdaEvent<SqsEvent>,
+    request_handling_fn: F,
+) -> SqsBatchResponse
+where
+    F: Fn(LambdaEvent<Request>) -> FutF,
+    FutF: Future<Output = Result<(), Error>>,
+{
+    let mut batch_item_failures = vec![];
+    for message in event.payload.records {
+        let request = match serde_json::from_str::<Request>(&message.body) {
+            Ok(request) => request,
+            Err(error) => {
+                // NOTE: a malformed body will never deserialize on redelivery,
+                // so it is dropped here instead of being reported for retry.
+                let mssg = format!(
+                    "Dropping SQS message {} with malformed body:\n{:?}",
+                    &message.message_id, error
+                );
+                event!(Level::ERROR, mssg);
+                continue;
+            }
+        };
+        let request_event = LambdaEvent::new(request, event.context.clone());
+        if let Err(error) = request_handling_fn(request_event).await {
+            let mssg = format!(
+                "Failed to process SQS message {}:\n{}",
+                &message.message_id, error
+            );
+            event!(Level::ERROR, mssg);
+            batch_item_failures.push(SqsBatchItemFailure {
+                item_identifier: message.message_id,
+            });
+        }
+    }
+    SqsBatchResponse {
+        batch_item_failures,
+    }
+}
+
+pub async fn sqs_handler(
+    event: LambdaEvent<SqsEvent>,
+    config: &Config,
+    dedup_store: &DedupStore,
+) -> Result<SqsBatchResponse, Error> {
+    let request_handling_fn = |request_event| async move {
+        handler(request_event, config, dedup_store)
+            .await
+            .map(|_| ())
+    };
+    Ok(process_sqs_batch(event, request_handling_fn).await)
+}
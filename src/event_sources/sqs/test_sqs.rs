@@ -0,0 +1,112 @@
+use super::*;
+use lambda_runtime::Context;
+use simple_error::simple_error;
+
+fn _build_request_body(event_id: &str, creator_id: &str) -> String {
+    serde_json::json!({
+        "source": "conductor.test",
+        "event_id": event_id,
+        "detail": {
+            "creator_type": "DataSource",
+            "creator_id": creator_id,
+            "payload": {
+                "event_time": "2023-09-21T10:05:00Z"
+            }
+        }
+    })
+    .to_string()
+}
+
+fn _build_sqs_event(messages: Vec<(String, String)>) -> LambdaEvent<SqsEvent> {
+    let records = messages
+        .into_iter()
+        .map(|(message_id, body)| SqsMessage { message_id, body })
+        .collect();
+    LambdaEvent::new(SqsEvent { records }, Context::default())
+}
+
+#[tokio::test]
+async fn process_sqs_batch_test_all_messages_succeed() {
+    let sqs_event = _build_sqs_event(vec![
+        (
+            String::from("message1"),
+            _build_request_body("event1", "source1"),
+        ),
+        (
+            String::from("message2"),
+            _build_request_body("event2", "source2"),
+        ),
+    ]);
+    let request_handling_fn = |_: LambdaEvent<Request>| async move { Ok(()) };
+    let response = process_sqs_batch(sqs_event, request_handling_fn).await;
+    assert_eq!(response.batch_item_failures.len(), 0);
+}
+
+#[tokio::test]
+async fn process_sqs_batch_test_reports_only_failed_messages() {
+    let failing_source_id = format!("source{}", rand::random::<u32>());
+    let sqs_event = _build_sqs_event(vec![
+        (
+            String::from("message1"),
+            _build_request_body("event1", "source1"),
+        ),
+        (
+            String::from("message2"),
+            _build_request_body("event2", &failing_source_id),
+        ),
+        (
+            String::from("message3"),
+            _build_request_body("event3", "source3"),
+        ),
+    ]);
+    let request_handling_fn = |request_event: LambdaEvent<Request>| {
+        let is_failing = request_event.payload.detail.creator_id == failing_source_id;
+        async move {
+            if is_failing {
+                Err(Error::from(simple_error!("pipeline failed to trigger")))
+            } else {
+                Ok(())
+            }
+        }
+    };
+    let response = process_sqs_batch(sqs_event, request_handling_fn).await;
+    assert_eq!(
+        response.batch_item_failures,
+        vec![SqsBatchItemFailure {
+            item_identifier: String::from("message2")
+        }]
+    );
+}
+
+#[tokio::test]
+async fn process_sqs_batch_test_drops_malformed_message() {
+    let sqs_event = _build_sqs_event(vec![
+        (
+            String::from("message1"),
+            String::from("{\"not\": \"a request\"}"),
+        ),
+        (
+            String::from("message2"),
+            _build_request_body("event2", "source2"),
+        ),
+    ]);
+    let request_handling_fn = |request_event: LambdaEvent<Request>| {
+        assert_eq!(request_event.payload.event_id, "event2");
+        async move { Ok(()) }
+    };
+    let response = process_sqs_batch(sqs_event, request_handling_fn).await;
+    assert_eq!(response.batch_item_failures.len(), 0);
+}
+
+#[test]
+fn sqs_batch_response_test_serialization() {
+    let response = SqsBatchResponse {
+        batch_item_failures: vec![SqsBatchItemFailure {
+            item_identifier: String::from("message1"),
+        }],
+    };
+    assert_eq!(
+        serde_json::to_value(&response).unwrap(),
+        serde_json::json!({"batchItemFailures": [{"itemIdentifier": "message1"}]})
+    );
+}
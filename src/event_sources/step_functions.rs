@@ -0,0 +1,81 @@
+use super::eventbridge::{RawRequestDetail, RawRequestPayload};
+use crate::events::versions::CURRENT_SCHEMA_VERSION;
+use crate::{RequestFailure, RequestType};
+use serde::Deserialize;
+use time::format_description::well_known::iso8601::Iso8601;
+use time::OffsetDateTime;
+
+pub const STEP_FUNCTIONS_STATUS_DETAIL_TYPE: &str = "Step Functions Execution Status Change";
+
+const FAILURE_STATUSES: [&str; 3] = ["FAILED", "TIMED_OUT", "ABORTED"];
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StepFunctionsStatusDetail {
+    #[serde(rename = "executionArn")]
+    execution_arn: String,
+    #[serde(rename = "stateMachineArn")]
+    state_machine_arn: String,
+    status: String,
+    #[serde(rename = "stopDate")]
+    stop_date: Option<i64>,
+    input: Option<String>,
+    error: Option<String>,
+    cause: Option<String>,
+}
+
+impl StepFunctionsStatusDetail {
+    // NOTE: executions that start or succeed are reported by the pipelines
+    // themselves, so these status changes are acknowledged without effects.
+    pub fn ignored_reason(&self) -> Option<String> {
+        if FAILURE_STATUSES.contains(&self.status.as_str()) {
+            return None;
+        }
+        Some(format!(
+            "Execution {} has status {}; only {:?} executions are handled.",
+            &self.execution_arn, &self.status, FAILURE_STATUSES
+        ))
+    }
+
+    pub fn into_raw_request_detail(self) -> Result<RawRequestDetail, String> {
+        let is_failure = FAILURE_STATUSES.contains(&self.status.as_str());
+        // NOTE: pipeline ids are the names of their state machines, which
+        // are the last segment of the state machine arn.
+        let pipeline_id = match self.state_machine_arn.rsplit(':').next() {
+            Some(name) if !name.is_empty() => String::from(name),
+            _ => {
+                return Err(format!(
+                    "Failed to find a state machine name in arn {}.",
+                    &self.state_machine_arn
+                ))
+            }
+        };
+        let event_time = match self.stop_date {
+            Some(stop_date) => Some(_format_epoch_millis(stop_date)?),
+            None => None,
+        };
+        Ok(RawRequestDetail {
+            schema_version: Some(CURRENT_SCHEMA_VERSION),
+            creator_type: Some(RequestType::PipelineFailure),
+            creator_id: pipeline_id,
+            payload: RawRequestPayload {
+                callback_token: None,
+                success_time: None,
+                event_time,
+                failure: is_failure.then_some(RequestFailure {
+                    status: self.status,
+                    error: self.error,
+                    cause: self.cause,
+                    state_machine_arn: Some(self.state_machine_arn),
+                    execution_input: self.input,
+                }),
+            },
+        })
+    }
+}
+
+fn _format_epoch_millis(epoch_millis: i64) -> Result<String, String> {
+    OffsetDateTime::from_unix_timestamp_nanos(epoch_millis as i128 * 1_000_000)
+        .map_err(|e| format!("Invalid stopDate {}: {}", epoch_millis, e))?
+        .format(&Iso8601::DEFAULT)
+        .map_err(|e| format!("Failed to format stopDate {}: {}", epoch_millis, e))
+}
@@ -1,34 +1,204 @@
-use crate::entities::{Event, EventPayload};
-use crate::Request;
+pub mod remote;
+#[cfg(test)]
+mod test_events;
+pub mod versions;
+
+use crate::config::Config;
+use crate::dry_run::{EffectRecorder, PlannedEffect};
+use crate::entities::{Event, EventPayload, PipelineFailure, PipelineFailureStatus};
+use crate::pipeline_effects::RemoteFailure;
+use crate::routing::Backend;
+use crate::{Request, RequestType};
 use lambda_runtime::{Error, LambdaEvent};
-use simple_error::simple_error;
+use remote::{get_events_path, persist_event};
+use std::fmt;
+use std::future::Future;
+use time::format_description::well_known::iso8601::Iso8601;
 use time::OffsetDateTime;
+use tracing::{event, Level};
+use versions::upgrade_request_detail;
 
 #[derive(Debug)]
 pub enum EventProcessingError {
     EventValidationError(String),
     EventInitializationError(String),
-    EventPersistingError(String),
+    EventPersistingError(RemoteFailure),
     EventTimeConversionError(String),
 }
 
+impl fmt::Display for EventProcessingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for EventProcessingError {}
+
+impl EventProcessingError {
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EventProcessingError::EventPersistingError(failure) => failure.is_transient,
+            _ => false,
+        }
+    }
+}
+
 pub async fn process_input_event(
-    _event: LambdaEvent<Request>,
+    event: LambdaEvent<Request>,
 ) -> Result<crate::entities::Event, EventProcessingError> {
-    // ommiting implementation from github.com sample version
-    // replacing with below hardcoded return value
-    Ok(Event {
-        id: String::from("event_id"),
-        event_time: OffsetDateTime::now_utc(),
-        payload: EventPayload::DataSource {
-            id: String::from("ds_id"),
+    let request = event.payload;
+    let mssg = format!(
+        "Processing event {} from source {}.",
+        &request.event_id, &request.source
+    );
+    event!(Level::INFO, mssg);
+    if let Some(envelope) = &request.envelope {
+        let mssg = format!(
+            "Event {} arrived in an EventBridge envelope with detail-type {} from account {:?} in region {:?}.",
+            &request.event_id, &envelope.detail_type, &envelope.account, &envelope.region
+        );
+        event!(Level::INFO, mssg);
+    }
+    let request = Request {
+        detail: upgrade_request_detail(request.detail)?,
+        ..request
+    };
+    let event_id = _get_required_field("event_id", Some(&request.event_id))?;
+    let creator_id = _get_required_field("detail.creator_id", Some(&request.detail.creator_id))?;
+    let request_payload = &request.detail.payload;
+    let event_time = _parse_event_time("detail.payload.event_time", &request_payload.event_time)?;
+    let payload = match request.detail.creator_type {
+        RequestType::DataSource => EventPayload::DataSource { id: creator_id },
+        RequestType::Pipeline => {
+            let success_time = _get_required_field(
+                "detail.payload.success_time",
+                request_payload.success_time.as_ref(),
+            )?;
+            let callback_token = _get_required_field(
+                "detail.payload.callback_token",
+                request_payload.callback_token.as_ref(),
+            )?;
+            EventPayload::DataPipeline {
+                id: creator_id,
+                success_time: _parse_event_time("detail.payload.success_time", &success_time)?,
+                callback_token,
+            }
+        }
+        RequestType::PipelineFailure => match &request_payload.failure {
+            Some(failure) => EventPayload::DataPipelineFailure {
+                id: creator_id,
+                failure: PipelineFailure {
+                    status: _parse_failure_status(&failure.status)?,
+                    error: failure.error.clone(),
+                    cause: failure.cause.clone(),
+                    state_machine_arn: failure.state_machine_arn.clone(),
+                    execution_input: failure.execution_input.clone(),
+                },
+            },
+            None => return Err(EventProcessingError::EventValidationError(String::from(
+                "Field detail.payload.failure is required for this creator type; found nothing.",
+            ))),
         },
+    };
+    Ok(Event {
+        id: event_id,
+        source: request.source,
+        event_time,
+        payload,
     })
 }
 
-pub async fn process_lambda_event(event: LambdaEvent<Request>) -> Result<Event, Error> {
+fn _get_required_field(
+    field_name: &str,
+    maybe_value: Option<&String>,
+) -> Result<String, EventProcessingError> {
+    match maybe_value {
+        Some(value) if !value.trim().is_empty() => Ok(value.to_string()),
+        Some(_) => Err(EventProcessingError::EventValidationError(format!(
+            "Field {} must not be empty.",
+            field_name
+        ))),
+        None => Err(EventProcessingError::EventValidationError(format!(
+            "Field {} is required for this creator type; found nothing.",
+            field_name
+        ))),
+    }
+}
+
+fn _parse_failure_status(status: &str) -> Result<PipelineFailureStatus, EventProcessingError> {
+    match status {
+        "FAILED" => Ok(PipelineFailureStatus::Failed),
+        "TIMED_OUT" => Ok(PipelineFailureStatus::TimedOut),
+        "ABORTED" => Ok(PipelineFailureStatus::Aborted),
+        other => Err(EventProcessingError::EventValidationError(format!(
+            "Field detail.payload.failure.status has unrecognized value {}.",
+            other
+        ))),
+    }
+}
+
+fn _parse_event_time(
+    field_name: &str,
+    dt_str: &str,
+) -> Result<OffsetDateTime, EventProcessingError> {
+    OffsetDateTime::parse(dt_str, &Iso8601::DEFAULT).map_err(|e| {
+        EventProcessingError::EventTimeConversionError(format!(
+            "Failed to parse field {} with value {:?} as an ISO 8601 datetime:\n{:?}",
+            field_name, dt_str, e
+        ))
+    })
+}
+
+pub(crate) async fn handle_lambda_event<F, FutF>(
+    event: LambdaEvent<Request>,
+    recorder: Option<&EffectRecorder>,
+    is_persisted: bool,
+    fn_persist_event: F,
+) -> Result<Event, Error>
+where
+    F: Fn(Event) -> FutF,
+    FutF: Future<Output = Result<Event, EventProcessingError>>,
+{
     let event = process_input_event(event.clone())
         .await
-        .map_err(|e| Box::new(simple_error!(&format!("{:?}", e))))?;
-    Ok(event)
+        .map_err(Error::from)?;
+    if let Some(recorder) = recorder {
+        let owner_id = match &event.payload {
+            EventPayload::DataSource { id }
+            | EventPayload::DataPipeline { id, .. }
+            | EventPayload::DataPipelineFailure { id, .. } => String::from(id),
+        };
+        recorder.record(
+            &owner_id,
+            PlannedEffect::PersistEvent {
+                event_id: String::from(&event.id),
+                path: get_events_path(&event).join("/"),
+            },
+        );
+        return Ok(event);
+    }
+    if is_persisted {
+        let mssg = format!(
+            "Event {} was persisted by an earlier delivery; skipping persisting.",
+            &event.id
+        );
+        event!(Level::INFO, mssg);
+        return Ok(event);
+    }
+    fn_persist_event(event).await.map_err(Error::from)
+}
+
+pub async fn process_lambda_event(
+    event: LambdaEvent<Request>,
+    config: &Config,
+    backend: &Backend,
+    recorder: Option<&EffectRecorder>,
+    is_persisted: bool,
+) -> Result<Event, Error> {
+    let fn_persist_event = |event: Event| async move {
+        persist_event(&event, backend, &config.clients)
+            .await
+            .map(|_| event)
+    };
+    handle_lambda_event(event, recorder, is_persisted, fn_persist_event).await
 }
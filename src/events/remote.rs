@@ -0,0 +1,130 @@
+use super::EventProcessingError;
+use crate::config::Clients;
+use crate::entities::{Event, EventPayload};
+use crate::pipeline_effects::endpoint::{construct_endpoint_url, write_rest_model};
+use crate::pipeline_effects::RemoteFailure;
+use crate::routing::Backend;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::format_description::well_known::iso8601::Iso8601;
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct EventRestModel {
+    pub id: String,
+    pub description: Option<String>,
+    pub event_time: String,
+    pub event_type: EventType,
+    pub raised_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks_downstream: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub enum EventType {
+    #[serde(rename = "data_source")]
+    DataSource,
+    #[serde(rename = "data_pipeline")]
+    DataPipeline,
+    #[serde(rename = "data_pipeline_failure")]
+    DataPipelineFailure,
+}
+
+pub async fn persist_event(
+    event: &Event,
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<(), EventProcessingError> {
+    let endpoint_url = construct_endpoint_url(&backend.endpoint_prefix, &get_events_path(event))
+        .map_err(|e| {
+            EventProcessingError::EventPersistingError(RemoteFailure::terminal(format!(
+                "Failed to construct events endpoint url for event {}:\n{:?}",
+                &event.id, e
+            )))
+        })?;
+    let body = serde_json::to_string(&build_event_rest_model(event)?).map_err(|e| {
+        EventProcessingError::EventPersistingError(RemoteFailure::terminal(format!(
+            "Failed to serialize event {}:\n{:?}",
+            &event.id, e
+        )))
+    })?;
+    let headers = HashMap::from([(
+        String::from("Content-Type"),
+        String::from("application/json"),
+    )]);
+    write_rest_model(
+        endpoint_url.as_str(),
+        backend,
+        clients,
+        body,
+        &headers,
+        "POST",
+    )
+    .await
+    .map_err(|e| {
+        let mssg = format!(
+            "Failed to persist event {} to remote api:\n{:?}",
+            &event.id, e
+        );
+        EventProcessingError::EventPersistingError(RemoteFailure {
+            reason: mssg,
+            is_transient: e.is_retryable(),
+        })
+    })
+}
+
+pub fn get_events_path(event: &Event) -> Vec<String> {
+    match &event.payload {
+        EventPayload::DataSource { id } => vec![
+            String::from("data-sources"),
+            String::from(id),
+            String::from("events"),
+        ],
+        EventPayload::DataPipeline { id, .. } | EventPayload::DataPipelineFailure { id, .. } => {
+            vec![
+                String::from("pipelines"),
+                String::from(id),
+                String::from("events"),
+            ]
+        }
+    }
+}
+
+pub fn build_event_rest_model(event: &Event) -> Result<EventRestModel, EventProcessingError> {
+    let event_time = event.event_time.format(&Iso8601::DEFAULT).map_err(|e| {
+        EventProcessingError::EventTimeConversionError(format!(
+            "Failed to format event time of event {}:\n{:?}",
+            &event.id, e
+        ))
+    })?;
+    let (event_type, description) = match &event.payload {
+        EventPayload::DataSource { id } => (
+            EventType::DataSource,
+            format!("New data available from data source {}.", id),
+        ),
+        EventPayload::DataPipeline {
+            id, success_time, ..
+        } => (
+            EventType::DataPipeline,
+            format!("Pipeline {} succeeded at {}.", id, success_time),
+        ),
+        EventPayload::DataPipelineFailure { id, failure } => (
+            EventType::DataPipelineFailure,
+            format!(
+                "Pipeline {} ended with status {:?}. Error: {}. Cause: {}.",
+                id,
+                failure.status,
+                failure.error.as_deref().unwrap_or("unknown"),
+                failure.cause.as_deref().unwrap_or("unknown")
+            ),
+        ),
+    };
+    Ok(EventRestModel {
+        id: String::from(&event.id),
+        description: Some(description),
+        event_time,
+        event_type,
+        raised_by: String::from(&event.source),
+        blocks_downstream: None,
+    })
+}
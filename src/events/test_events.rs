@@ -0,0 +1,372 @@
+use super::*;
+use crate::config::test_config::build_sdk_config;
+use crate::{RequestDetail, RequestPayload};
+use lambda_runtime::Context;
+use rand::thread_rng;
+use rand::{self, Rng};
+use std::num::NonZeroU8;
+use std::sync::Mutex;
+use time::format_description::well_known::iso8601::{self, EncodedConfig, Iso8601, TimePrecision};
+use versions::CURRENT_SCHEMA_VERSION;
+
+const DT_FMT: EncodedConfig = iso8601::Config::DEFAULT
+    .set_time_precision(TimePrecision::Second {
+        decimal_digits: NonZeroU8::new(6),
+    })
+    .encode();
+
+fn _build_lambda_event(
+    creator_type: RequestType,
+    creator_id: String,
+    payload: RequestPayload,
+) -> LambdaEvent<Request> {
+    let request = Request {
+        source: String::from("conductor.test"),
+        event_id: format!("event{}", rand::random::<u32>()),
+        envelope: None,
+        ignored_reason: None,
+        detail: RequestDetail {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            creator_type,
+            creator_id,
+            payload,
+        },
+    };
+    LambdaEvent::new(request, Context::default())
+}
+
+#[tokio::test]
+async fn process_input_event_test_happy_path_data_source() {
+    let rand_num_source_id = rand::random::<u32>();
+    let mut rng = thread_rng();
+    let unix_ts = rng.gen_range(338250323..1663680521);
+    let dt = OffsetDateTime::from_unix_timestamp(unix_ts).unwrap();
+    let lambda_event = _build_lambda_event(
+        RequestType::DataSource,
+        format!("source{}", rand_num_source_id),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: dt.format(&Iso8601::<DT_FMT>).unwrap(),
+            failure: None,
+        },
+    );
+    let expected_event_id = lambda_event.payload.event_id.to_string();
+    let result = process_input_event(lambda_event).await;
+    assert!(result.is_ok());
+    let event = result.unwrap();
+    assert_eq!(event.id, expected_event_id);
+    assert_eq!(event.event_time, dt);
+    if let EventPayload::DataSource { id } = event.payload {
+        assert_eq!(id, format!("source{}", rand_num_source_id));
+    } else {
+        panic!("expected a data source event payload");
+    };
+}
+
+#[tokio::test]
+async fn process_input_event_test_happy_path_pipeline() {
+    let rand_num_pipeline_id = rand::random::<u32>();
+    let rand_num_token = rand::random::<u32>();
+    let mut rng = thread_rng();
+    let unix_ts = rng.gen_range(338250323..1663680521);
+    let event_dt = OffsetDateTime::from_unix_timestamp(unix_ts).unwrap();
+    let success_dt = OffsetDateTime::from_unix_timestamp(unix_ts - 60).unwrap();
+    let lambda_event = _build_lambda_event(
+        RequestType::Pipeline,
+        format!("pipeline{}", rand_num_pipeline_id),
+        RequestPayload {
+            callback_token: Some(format!("token{}", rand_num_token)),
+            success_time: Some(success_dt.format(&Iso8601::<DT_FMT>).unwrap()),
+            event_time: event_dt.format(&Iso8601::<DT_FMT>).unwrap(),
+            failure: None,
+        },
+    );
+    let result = process_input_event(lambda_event).await;
+    assert!(result.is_ok());
+    let event = result.unwrap();
+    assert_eq!(event.event_time, event_dt);
+    if let EventPayload::DataPipeline {
+        id,
+        success_time,
+        callback_token,
+    } = event.payload
+    {
+        assert_eq!(id, format!("pipeline{}", rand_num_pipeline_id));
+        assert_eq!(success_time, success_dt);
+        assert_eq!(callback_token, format!("token{}", rand_num_token));
+    } else {
+        panic!("expected a data pipeline event payload");
+    };
+}
+
+#[tokio::test]
+async fn process_input_event_test_pipeline_missing_callback_token() {
+    let lambda_event = _build_lambda_event(
+        RequestType::Pipeline,
+        format!("pipeline{}", rand::random::<u32>()),
+        RequestPayload {
+            callback_token: None,
+            success_time: Some(String::from("2023-09-21T10:00:00Z")),
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    let result = process_input_event(lambda_event).await;
+    if let Err(EventProcessingError::EventValidationError(s)) = result {
+        assert!(s.contains("detail.payload.callback_token"));
+    } else {
+        panic!("expected an event validation error");
+    };
+}
+
+#[tokio::test]
+async fn process_input_event_test_empty_creator_id() {
+    let lambda_event = _build_lambda_event(
+        RequestType::DataSource,
+        String::from("  "),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    let result = process_input_event(lambda_event).await;
+    if let Err(EventProcessingError::EventValidationError(s)) = result {
+        assert!(s.contains("detail.creator_id"));
+    } else {
+        panic!("expected an event validation error");
+    };
+}
+
+#[tokio::test]
+async fn process_input_event_test_malformed_success_time() {
+    let lambda_event = _build_lambda_event(
+        RequestType::Pipeline,
+        format!("pipeline{}", rand::random::<u32>()),
+        RequestPayload {
+            callback_token: Some(String::from("token")),
+            success_time: Some(String::from("yesterday at noon")),
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    let result = process_input_event(lambda_event).await;
+    if let Err(EventProcessingError::EventTimeConversionError(s)) = result {
+        assert!(s.contains("detail.payload.success_time"));
+        assert!(s.contains("yesterday at noon"));
+    } else {
+        panic!("expected an event time conversion error");
+    };
+}
+
+#[tokio::test]
+async fn process_input_event_test_malformed_event_time() {
+    let lambda_event = _build_lambda_event(
+        RequestType::DataSource,
+        format!("source{}", rand::random::<u32>()),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-13-45"),
+            failure: None,
+        },
+    );
+    let result = process_input_event(lambda_event).await;
+    if let Err(EventProcessingError::EventTimeConversionError(s)) = result {
+        assert!(s.contains("detail.payload.event_time"));
+    } else {
+        panic!("expected an event time conversion error");
+    };
+}
+
+#[test]
+fn get_events_path_test_data_source_and_pipeline() {
+    let rand_num_id = rand::random::<u32>();
+    let data_source_event = Event {
+        id: String::from("event"),
+        source: String::from("conductor.test"),
+        event_time: OffsetDateTime::now_utc(),
+        payload: EventPayload::DataSource {
+            id: format!("source{}", rand_num_id),
+        },
+    };
+    assert_eq!(
+        remote::get_events_path(&data_source_event),
+        vec![
+            String::from("data-sources"),
+            format!("source{}", rand_num_id),
+            String::from("events")
+        ]
+    );
+    let pipeline_event = Event {
+        id: String::from("event"),
+        source: String::from("conductor.test"),
+        event_time: OffsetDateTime::now_utc(),
+        payload: EventPayload::DataPipeline {
+            id: format!("pipeline{}", rand_num_id),
+            success_time: OffsetDateTime::now_utc(),
+            callback_token: String::from("token"),
+        },
+    };
+    assert_eq!(
+        remote::get_events_path(&pipeline_event),
+        vec![
+            String::from("pipelines"),
+            format!("pipeline{}", rand_num_id),
+            String::from("events")
+        ]
+    );
+}
+
+#[test]
+fn build_event_rest_model_test_happy_path() {
+    let rand_num_event_id = rand::random::<u32>();
+    let rand_num_source_id = rand::random::<u32>();
+    let mut rng = thread_rng();
+    let unix_ts = rng.gen_range(338250323..1663680521);
+    let dt = OffsetDateTime::from_unix_timestamp(unix_ts).unwrap();
+    let event = Event {
+        id: format!("event{}", rand_num_event_id),
+        source: String::from("conductor.test"),
+        event_time: dt,
+        payload: EventPayload::DataSource {
+            id: format!("source{}", rand_num_source_id),
+        },
+    };
+    let model = remote::build_event_rest_model(&event).unwrap();
+    assert_eq!(model.id, format!("event{}", rand_num_event_id));
+    assert_eq!(model.event_type, remote::EventType::DataSource);
+    assert_eq!(model.raised_by, "conductor.test");
+    assert_eq!(
+        OffsetDateTime::parse(&model.event_time, &Iso8601::DEFAULT).unwrap(),
+        dt
+    );
+    let serialized = serde_json::to_value(&model).unwrap();
+    assert_eq!(serialized["event_type"], "data_source");
+}
+
+#[tokio::test]
+async fn process_input_event_test_happy_path_pipeline_failure() {
+    let rand_num_pipeline_id = rand::random::<u32>();
+    let mut lambda_event = _build_lambda_event(
+        RequestType::PipelineFailure,
+        format!("pipeline{}", rand_num_pipeline_id),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    lambda_event.payload.detail.payload.failure = Some(crate::RequestFailure {
+        status: String::from("ABORTED"),
+        error: None,
+        cause: Some(String::from("operator aborted execution")),
+        state_machine_arn: None,
+        execution_input: None,
+    });
+    let event = process_input_event(lambda_event).await.unwrap();
+    if let EventPayload::DataPipelineFailure { id, failure } = event.payload {
+        assert_eq!(id, format!("pipeline{}", rand_num_pipeline_id));
+        assert_eq!(failure.status, PipelineFailureStatus::Aborted);
+        assert_eq!(
+            failure.cause,
+            Some(String::from("operator aborted execution"))
+        );
+    } else {
+        panic!("expected a data pipeline failure event payload");
+    };
+}
+
+#[tokio::test]
+async fn process_input_event_test_pipeline_failure_missing_failure() {
+    let lambda_event = _build_lambda_event(
+        RequestType::PipelineFailure,
+        format!("pipeline{}", rand::random::<u32>()),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    let result = process_input_event(lambda_event).await;
+    if let Err(EventProcessingError::EventValidationError(s)) = result {
+        assert!(s.contains("detail.payload.failure"));
+    } else {
+        panic!("expected an event validation error");
+    };
+}
+
+#[tokio::test]
+async fn process_lambda_event_test_dry_run_records_persisting() {
+    let env_vars = std::collections::HashMap::from([
+        (
+            String::from("ENV_ENDPOINT_URL"),
+            String::from("http://127.0.0.1:9/"),
+        ),
+        (String::from("ENV_AWS_REGION"), String::from("eu-west-1")),
+    ]);
+    let config = Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap();
+    let source_id = format!("source{}", rand::random::<u32>());
+    let lambda_event = _build_lambda_event(
+        RequestType::DataSource,
+        String::from(&source_id),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    let event_id = String::from(&lambda_event.payload.event_id);
+    let recorder = EffectRecorder::new();
+    process_lambda_event(
+        lambda_event,
+        &config,
+        &config.default_backend(),
+        Some(&recorder),
+        false,
+    )
+    .await
+    .unwrap();
+    let plan = recorder.into_plan(&event_id);
+    assert_eq!(
+        plan.pipelines.get(&source_id),
+        Some(&vec![PlannedEffect::PersistEvent {
+            event_id: String::from(&event_id),
+            path: format!("data-sources/{}/events", &source_id),
+        }])
+    );
+}
+
+#[tokio::test]
+async fn handle_lambda_event_test_skips_persisting_persisted_event() {
+    let source_id = format!("source{}", rand::random::<u32>());
+    let lambda_event = _build_lambda_event(
+        RequestType::DataSource,
+        String::from(&source_id),
+        RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    );
+    let persisted = Mutex::new(vec![]);
+    let fn_persist_event = |event: Event| {
+        persisted.lock().unwrap().push(String::from(&event.id));
+        async move { Ok(event) }
+    };
+    let event = handle_lambda_event(lambda_event.clone(), None, true, &fn_persist_event)
+        .await
+        .unwrap();
+    assert!(matches!(event.payload, EventPayload::DataSource { id } if id == source_id));
+    assert!(persisted.lock().unwrap().is_empty());
+    let event = handle_lambda_event(lambda_event, None, false, &fn_persist_event)
+        .await
+        .unwrap();
+    assert_eq!(persisted.into_inner().unwrap(), vec![event.id]);
+}
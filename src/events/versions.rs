@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test_versions;
+
+use super::EventProcessingError;
+use crate::{RequestDetail, RequestType};
+
+// NOTE: requests without a schema_version predate versioning and are
+// decoded as the legacy version.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+pub fn upgrade_request_detail(
+    detail: RequestDetail,
+) -> Result<RequestDetail, EventProcessingError> {
+    let mut detail = detail;
+    while detail.schema_version < CURRENT_SCHEMA_VERSION {
+        detail = match detail.schema_version {
+            1 => _upgrade_v1_to_v2(detail)?,
+            other => return Err(_unsupported_version_error(other)),
+        };
+    }
+    if detail.schema_version > CURRENT_SCHEMA_VERSION {
+        return Err(_unsupported_version_error(detail.schema_version));
+    }
+    Ok(detail)
+}
+
+fn _unsupported_version_error(schema_version: u32) -> EventProcessingError {
+    EventProcessingError::EventValidationError(format!(
+        "Field detail.schema_version has unsupported value {}. Supported versions are {} through {}.",
+        schema_version, LEGACY_SCHEMA_VERSION, CURRENT_SCHEMA_VERSION
+    ))
+}
+
+// v2 added pipeline failure events; v1 requests can only describe
+// data source events and pipeline successes.
+fn _upgrade_v1_to_v2(detail: RequestDetail) -> Result<RequestDetail, EventProcessingError> {
+    if detail.creator_type == RequestType::PipelineFailure || detail.payload.failure.is_some() {
+        return Err(EventProcessingError::EventValidationError(String::from(
+            "Pipeline failure events require detail.schema_version 2 or later.",
+        )));
+    }
+    Ok(RequestDetail {
+        schema_version: 2,
+        ..detail
+    })
+}
@@ -0,0 +1,66 @@
+use super::*;
+use crate::{RequestFailure, RequestPayload};
+
+fn _build_request_detail(schema_version: u32, creator_type: RequestType) -> RequestDetail {
+    RequestDetail {
+        schema_version,
+        creator_type,
+        creator_id: format!("creator{}", rand::random::<u32>()),
+        payload: RequestPayload {
+            callback_token: None,
+            success_time: None,
+            event_time: String::from("2023-09-21T10:05:00Z"),
+            failure: None,
+        },
+    }
+}
+
+#[test]
+fn upgrade_request_detail_test_current_version_is_unchanged() {
+    let detail = _build_request_detail(CURRENT_SCHEMA_VERSION, RequestType::PipelineFailure);
+    let creator_id = detail.creator_id.to_string();
+    let upgraded = upgrade_request_detail(detail).unwrap();
+    assert_eq!(upgraded.schema_version, CURRENT_SCHEMA_VERSION);
+    assert_eq!(upgraded.creator_id, creator_id);
+}
+
+#[test]
+fn upgrade_request_detail_test_legacy_version_is_upgraded() {
+    let detail = _build_request_detail(LEGACY_SCHEMA_VERSION, RequestType::DataSource);
+    let creator_id = detail.creator_id.to_string();
+    let upgraded = upgrade_request_detail(detail).unwrap();
+    assert_eq!(upgraded.schema_version, CURRENT_SCHEMA_VERSION);
+    assert_eq!(upgraded.creator_type, RequestType::DataSource);
+    assert_eq!(upgraded.creator_id, creator_id);
+}
+
+#[test]
+fn upgrade_request_detail_test_legacy_version_rejects_failures() {
+    let mut detail = _build_request_detail(LEGACY_SCHEMA_VERSION, RequestType::Pipeline);
+    detail.payload.failure = Some(RequestFailure {
+        status: String::from("FAILED"),
+        error: None,
+        cause: None,
+        state_machine_arn: None,
+        execution_input: None,
+    });
+    let result = upgrade_request_detail(detail);
+    if let Err(EventProcessingError::EventValidationError(s)) = result {
+        assert!(s.contains("schema_version 2"));
+    } else {
+        panic!("expected an event validation error");
+    };
+}
+
+#[test]
+fn upgrade_request_detail_test_unknown_versions() {
+    for schema_version in [0, CURRENT_SCHEMA_VERSION + 1] {
+        let detail = _build_request_detail(schema_version, RequestType::DataSource);
+        let result = upgrade_request_detail(detail);
+        if let Err(EventProcessingError::EventValidationError(s)) = result {
+            assert!(s.contains(&format!("unsupported value {}", schema_version)));
+        } else {
+            panic!("expected an event validation error");
+        };
+    }
+}
@@ -0,0 +1,238 @@
+pub mod cli;
+pub mod config;
+pub mod dedup;
+pub mod dry_run;
+pub mod entities;
+pub mod event_sources;
+pub mod events;
+pub mod pipeline_effects;
+pub mod response;
+pub mod routing;
+pub mod schema;
+pub mod signing;
+#[cfg(test)]
+mod test_handler;
+
+pub use pipeline_effects::rules::{TriggerRule, TriggerRuleParameters, TriggerRuleRegistry};
+
+use config::{Config, HandlerMode};
+use dedup::{DedupStore, ProcessingOutcome};
+use dry_run::EffectRecorder;
+use event_sources::eventbridge::{EventBridgeMetadata, RawRequest};
+use event_sources::s3::S3SourceMapping;
+use events::process_lambda_event;
+use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use pipeline_effects::{
+    decisions::PipelineDecision, evaluation_deadline, generate_pipeline_effects,
+};
+use response::HandlerResponse;
+use routing::Backend;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use simple_error::simple_error;
+use std::future::Future;
+use tracing::{event, Level};
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+enum RequestType {
+    DataSource,
+    Pipeline,
+    PipelineFailure,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "RawRequest")]
+pub struct Request {
+    source: String,
+    event_id: String,
+    envelope: Option<EventBridgeMetadata>,
+    // NOTE: set for well-formed requests that have no effects, which are
+    // acknowledged instead of failing and being redelivered.
+    ignored_reason: Option<String>,
+    detail: RequestDetail,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RequestDetail {
+    schema_version: u32,
+    creator_type: RequestType,
+    creator_id: String,
+    payload: RequestPayload,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RequestPayload {
+    callback_token: Option<String>,
+    success_time: Option<String>,
+    event_time: String,
+    failure: Option<RequestFailure>,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone)]
+pub struct RequestFailure {
+    status: String,
+    error: Option<String>,
+    cause: Option<String>,
+    state_machine_arn: Option<String>,
+    execution_input: Option<String>,
+}
+
+pub async fn handler(
+    event: LambdaEvent<Request>,
+    config: &Config,
+    dedup_store: &DedupStore,
+) -> Result<HandlerResponse, Error> {
+    let event_id = event.payload.event_id.to_string();
+    if config.dry_run {
+        // NOTE: dry runs neither consult nor update the dedup store, so a
+        // plan can be computed for events that were already processed.
+        let recorder = EffectRecorder::new();
+        let result = process_event(event, config, Some(&recorder), false).await;
+        let plan = recorder.into_plan(&event_id);
+        let mssg = format!(
+            "Dry run of event {} planned effects: {}",
+            &event_id,
+            serde_json::to_string(&plan).unwrap_or_default()
+        );
+        event!(Level::INFO, mssg);
+        return HandlerResponse::from_dry_run(&event_id, result, plan)?.into_result();
+    }
+    let backend = config.resolve_backend(&event.payload.source, &event.payload.detail.creator_id);
+    let fn_process_event = |event, is_persisted| process_event(event, config, None, is_persisted);
+    handle_deduplicated_event(event, &backend, dedup_store, fn_process_event).await
+}
+
+async fn handle_deduplicated_event<F, FutF>(
+    event: LambdaEvent<Request>,
+    backend: &Backend,
+    dedup_store: &DedupStore,
+    fn_process_event: F,
+) -> Result<HandlerResponse, Error>
+where
+    F: Fn(LambdaEvent<Request>, bool) -> FutF,
+    FutF: Future<Output = Result<Vec<PipelineDecision>, Error>>,
+{
+    let event_id = event.payload.event_id.to_string();
+    let recorded_outcome = dedup_store
+        .fetch_outcome(&event_id, backend)
+        .await
+        .map_err(|e| Box::new(simple_error!(format!("{:?}", e))))?;
+    // NOTE: only successful outcomes short-circuit a replay. Failed
+    // outcomes are recorded but reprocessed, otherwise Lambda retries
+    // could never recover from a transient failure; the event itself is
+    // not persisted again if an earlier delivery already did so.
+    let is_persisted = matches!(
+        recorded_outcome,
+        Some(ProcessingOutcome::Failed {
+            is_persisted: true,
+            ..
+        })
+    );
+    if let Some(ProcessingOutcome::Succeeded { response }) = recorded_outcome {
+        let mssg = format!(
+            "Event {} was already processed; skipping replay.",
+            &event_id
+        );
+        event!(Level::INFO, mssg);
+        return Ok(match response {
+            Some(response) => HandlerResponse {
+                replayed: true,
+                ..*response
+            },
+            None => HandlerResponse::replayed(&event_id),
+        });
+    }
+    let result =
+        HandlerResponse::from_result(&event_id, fn_process_event(event, is_persisted).await);
+    // NOTE: pipeline effects are only computed after the event was
+    // persisted, so any response that was not rejected means it was.
+    let outcome = match &result {
+        Ok(response) if response.is_success() => ProcessingOutcome::Succeeded {
+            response: Some(Box::new(response.clone())),
+        },
+        Ok(response) => ProcessingOutcome::Failed {
+            reason: serde_json::to_string(response).unwrap_or(format!("{:?}", response)),
+            is_persisted: is_persisted || response.rejected.is_none(),
+        },
+        Err(error) => ProcessingOutcome::Failed {
+            reason: format!("{}", error),
+            is_persisted,
+        },
+    };
+    if let Err(error) = dedup_store
+        .record_outcome(&event_id, &outcome, backend)
+        .await
+    {
+        let mssg = format!(
+            "Failed to record outcome of event {}:\n{:?}",
+            &event_id, error
+        );
+        event!(Level::WARN, mssg);
+    }
+    if let Ok(response) = &result {
+        if !response.is_success() && !response.is_retryable() {
+            let mssg = format!(
+                "Event {} failed with terminal errors; acknowledging without retry.",
+                &event_id
+            );
+            event!(Level::WARN, mssg);
+        }
+    }
+    result.and_then(HandlerResponse::into_result)
+}
+
+pub(crate) async fn process_event(
+    event: LambdaEvent<Request>,
+    config: &Config,
+    recorder: Option<&EffectRecorder>,
+    is_persisted: bool,
+) -> Result<Vec<PipelineDecision>, Error> {
+    if let Some(reason) = &event.payload.ignored_reason {
+        let mssg = format!("Ignoring event {}: {}", &event.payload.event_id, reason);
+        event!(Level::INFO, mssg);
+        return Ok(vec![]);
+    }
+    let backend = config.resolve_backend(&event.payload.source, &event.payload.detail.creator_id);
+    let deadline = evaluation_deadline(&event.context, config.deadline_margin);
+    let processed_event =
+        process_lambda_event(event, config, &backend, recorder, is_persisted).await?;
+    generate_pipeline_effects(processed_event, config, &backend, recorder, deadline)
+        .await
+        .map_err(Error::from)
+}
+
+// NOTE: the entry point for binaries that customize the config, e.g. to
+// register their own trigger rules, before handing it to the Lambda runtime.
+pub async fn run_lambda(config: Config) -> Result<(), Error> {
+    let config = &config;
+    let dedup_store = DedupStore::from_config(config);
+    let dedup_store = &dedup_store;
+    let s3_source_mapping = S3SourceMapping::from_rules(config.s3_source_mapping.clone());
+    let s3_source_mapping = &s3_source_mapping;
+    match config.handler_mode {
+        HandlerMode::Direct => {
+            run(service_fn(move |event| async move {
+                handler(event, config, dedup_store).await
+            }))
+            .await
+        }
+        HandlerMode::Sqs => {
+            run(service_fn(move |event| async move {
+                event_sources::sqs::sqs_handler(event, config, dedup_store).await
+            }))
+            .await
+        }
+        HandlerMode::S3 => {
+            run(service_fn(move |event| async move {
+                event_sources::s3::s3_handler(event, config, s3_source_mapping, dedup_store).await
+            }))
+            .await
+        }
+        HandlerMode::Schedule => {
+            run(service_fn(move |event| async move {
+                event_sources::schedule::schedule_handler(event, config).await
+            }))
+            .await
+        }
+    }
+}
@@ -1,17 +1,39 @@
-mod endpoint;
+pub mod decisions;
+pub mod endpoint;
+mod expressions;
+mod failures;
 mod gathering;
+pub mod rules;
+#[cfg(test)]
+mod test_pipeline_effects;
 mod triggering;
 
-use crate::entities::Event;
-use gathering::get_data_pipelines;
-use lambda_runtime::Error;
-use reqwest::Client;
-use simple_error::simple_error;
+use crate::config::{Clients, Config};
+use crate::dry_run::EffectRecorder;
+use crate::entities::{Event, EventPayload};
+use crate::routing::Backend;
+use aws_sdk_sfn::error::ProvideErrorMetadata;
+use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::result::SdkError;
+use decisions::{PipelineDecision, Verdict};
+use failures::apply_failure_policy;
+use futures::stream::{self, StreamExt};
+use gathering::{
+    get_data_pipelines, get_waiting_data_pipelines, DataSourceRestModel, PipelineRestModel,
+};
+use lambda_runtime::Context;
+use rules::TriggerRule;
+use schemars::{schema::RootSchema, schema_for};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use time::OffsetDateTime;
+use tracing::{event, Level};
 use triggering::maybe_trigger_pipeline;
 use url::Url;
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", content = "message")]
 pub enum ProcessingError {
     UnrecognizedTriggerType(String),
     MissingSuccessTime(String),
@@ -20,57 +42,358 @@ pub enum ProcessingError {
     PermitContentConflict(String),
     DatatimeParseFailure(String),
     UrlParseFailure(String),
-    ModelFetchFailure(String),
+    ModelFetchFailure(RemoteFailure),
     MissingPipelinePermit(String),
-    StateMachineFetchingError(String),
-    RelayTaskSuccessError(String),
-    RelayTaskHeartbeatError(String),
+    StateMachineFetchingError(RemoteFailure),
+    RelayTaskSuccessError(RemoteFailure),
+    RelayTaskHeartbeatError(RemoteFailure),
+    RelayTaskFailureError(RemoteFailure),
     PipelineStateMachineMissing(String),
+    UnrecognizedFailurePolicy(String),
+    PipelineRetryError(RemoteFailure),
+    InvalidQuorumThreshold(String),
+    InvalidDependencyExpression(String),
+}
+
+impl ProcessingError {
+    pub fn is_retryable(&self) -> bool {
+        // NOTE: only network failures, throttling and 5xx responses can
+        // succeed on redelivery; everything else fails the same way again.
+        match self {
+            ProcessingError::ModelFetchFailure(failure)
+            | ProcessingError::StateMachineFetchingError(failure)
+            | ProcessingError::RelayTaskSuccessError(failure)
+            | ProcessingError::RelayTaskHeartbeatError(failure)
+            | ProcessingError::RelayTaskFailureError(failure)
+            | ProcessingError::PipelineRetryError(failure) => failure.is_transient,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RemoteFailure {
+    pub reason: String,
+    pub is_transient: bool,
+}
+
+impl RemoteFailure {
+    pub fn transient(reason: String) -> RemoteFailure {
+        RemoteFailure {
+            reason,
+            is_transient: true,
+        }
+    }
+
+    pub fn terminal(reason: String) -> RemoteFailure {
+        RemoteFailure {
+            reason,
+            is_transient: false,
+        }
+    }
+}
+
+pub fn is_transient_sdk_error<E: ProvideErrorMetadata>(
+    error: &SdkError<E, http::Response<SdkBody>>,
+) -> bool {
+    match error {
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            true
+        }
+        SdkError::ServiceError(service_error) => {
+            let status = service_error.raw().status();
+            let is_throttled = service_error
+                .err()
+                .code()
+                .map(|code| code.contains("Throttl") || code == "TooManyRequestsException")
+                .unwrap_or(false);
+            status.is_server_error() || status.as_u16() == 429 || is_throttled
+        }
+        _ => false,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PipelineError {
+    // NOTE: None for failures that happen before any single pipeline
+    // is involved, e.g. while fetching the data source of the event.
+    pub pipeline_id: Option<String>,
+    pub error: ProcessingError,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineEffectsError {
+    pub failures: Vec<PipelineError>,
+    pub decisions: Vec<PipelineDecision>,
+}
+
+impl fmt::Display for PipelineEffectsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failure_strings: Vec<String> = self
+            .failures
+            .iter()
+            .map(|failure| {
+                format!(
+                    "{}: {:?}",
+                    failure.pipeline_id.as_deref().unwrap_or("<event>"),
+                    failure.error
+                )
+            })
+            .collect();
+        write!(
+            f,
+            "{} pipeline effects failed:\n{}",
+            self.failures.len(),
+            failure_strings.join("\n")
+        )
+    }
+}
+
+impl std::error::Error for PipelineEffectsError {}
+
+impl PipelineEffectsError {
+    fn _from_event_failure(pipeline_id: Option<&str>, error: ProcessingError) -> Self {
+        PipelineEffectsError {
+            failures: vec![PipelineError {
+                pipeline_id: pipeline_id.map(String::from),
+                error,
+            }],
+            decisions: vec![],
+        }
+    }
 }
 
 pub struct DataPipeline {
     id: String,
     description: String,
     last_success_time: Option<OffsetDateTime>,
-    permit: TriggerPermitType,
+    permit: Option<PipelineTriggerPermit>,
+    trigger_rule: Box<dyn TriggerRule>,
     dependency_urls: Vec<Url>,
+    // NOTE: one entry per dependency url; events before the window start
+    // never make the dependency fresh.
+    dependency_window_starts: Vec<Option<OffsetDateTime>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FailurePolicy {
+    Retry,
+    Notify,
+    Block,
 }
 
-enum TriggerPermitType {
-    Lenient(Option<PipelineTriggerPermit>),
-    Strict(Option<PipelineTriggerPermit>),
+// NOTE: the clients and settings shared by every pipeline evaluated for one
+// event or sweep.
+#[derive(Clone, Copy)]
+struct EffectContext<'a> {
+    config: &'a Config,
+    backend: &'a Backend,
+    clients: &'a Clients,
+    sfn_client: &'a aws_sdk_sfn::Client,
+    recorder: Option<&'a EffectRecorder>,
 }
 
 struct PipelineTriggerPermit {
     content: String,
+    expires_at: Option<OffsetDateTime>,
     is_expired: bool,
+    // NOTE: true once the expiry was recorded through the API, so it is
+    // recorded (and a fresh permit requested) only once.
+    is_expiry_recorded: bool,
+}
+
+pub fn evaluation_deadline(context: &Context, margin: Duration) -> Option<SystemTime> {
+    // NOTE: contexts that do not come from the Lambda runtime, e.g. in the
+    // CLI, carry no deadline.
+    if context.deadline == 0 {
+        return None;
+    }
+    let deadline = UNIX_EPOCH + Duration::from_millis(context.deadline);
+    Some(deadline.checked_sub(margin).unwrap_or(UNIX_EPOCH))
 }
 
 pub async fn generate_pipeline_effects(
     event: Event,
-    endpoint_prefix: &str,
-    aws_region: &str,
-) -> Result<(), Error> {
-    let mut error_strings = vec![];
-    let client = Client::new();
-    let relevant_pipelines =
-        get_data_pipelines(event.payload, endpoint_prefix, aws_region, &client)
+    config: &Config,
+    backend: &Backend,
+    recorder: Option<&EffectRecorder>,
+    deadline: Option<SystemTime>,
+) -> Result<Vec<PipelineDecision>, PipelineEffectsError> {
+    let clients = &config.clients;
+    let context = EffectContext {
+        config,
+        backend,
+        clients,
+        sfn_client: config.clients.sfn(backend),
+        recorder,
+    };
+    if let EventPayload::DataPipelineFailure { id, failure } = &event.payload {
+        return apply_failure_policy(&event.id, id, failure, context)
             .await
-            .map_err(|e| Box::new(simple_error!(format!("{:?}", e))))?;
-    for data_pipeline in &relevant_pipelines {
-        match maybe_trigger_pipeline(data_pipeline, aws_region, &client).await {
-            Ok(_) => {}
-            Err(error) => {
-                error_strings.push(format!("{:?}", error));
-            }
-        }
+            .map(|_| vec![])
+            .map_err(|e| PipelineEffectsError::_from_event_failure(Some(id), e));
     }
-    if error_strings.len() > 0 {
-        Err(Box::new(simple_error!(format!(
-            "Not all relevant pipelines were successfully triggered.\n{:?}",
-            error_strings
-        ))))
+    let pipeline_id = match &event.payload {
+        EventPayload::DataPipeline { id, .. } => Some(String::from(id)),
+        _ => None,
+    };
+    let relevant_pipelines = get_data_pipelines(
+        event.payload,
+        backend,
+        clients,
+        &config.trigger_rules,
+        config.max_concurrency,
+    )
+    .await
+    .map_err(|e| PipelineEffectsError::_from_event_failure(pipeline_id.as_deref(), e))?;
+    let decisions = _evaluate_pipelines(
+        relevant_pipelines
+            .iter()
+            .filter_map(|maybe| maybe.as_ref().ok()),
+        context,
+        deadline,
+    )
+    .await;
+    let failures: Vec<PipelineError> = relevant_pipelines
+        .iter()
+        .filter_map(|maybe| maybe.as_ref().err().cloned())
+        .chain(decisions.iter().filter_map(|decision| {
+            decision.error.clone().map(|error| PipelineError {
+                pipeline_id: Some(String::from(&decision.pipeline_id)),
+                error,
+            })
+        }))
+        .collect();
+    if failures.is_empty() {
+        Ok(decisions)
     } else {
-        Ok(())
+        Err(PipelineEffectsError {
+            failures,
+            decisions,
+        })
     }
 }
+
+pub async fn sweep_pipelines(
+    config: &Config,
+    recorder: Option<&EffectRecorder>,
+    deadline: Option<SystemTime>,
+) -> Result<Vec<PipelineDecision>, PipelineEffectsError> {
+    let mut failures = vec![];
+    let mut decisions = vec![];
+    let mut pipeline_count = 0;
+    let clients = &config.clients;
+    for backend in config.backends() {
+        let waiting_pipelines =
+            match get_waiting_data_pipelines(&backend, clients, &config.trigger_rules).await {
+                Ok(waiting_pipelines) => waiting_pipelines,
+                Err(error) => {
+                    failures.push(PipelineError {
+                        pipeline_id: None,
+                        error,
+                    });
+                    continue;
+                }
+            };
+        pipeline_count += waiting_pipelines.len();
+        failures.extend(
+            waiting_pipelines
+                .iter()
+                .filter_map(|maybe| maybe.as_ref().err().cloned()),
+        );
+        let context = EffectContext {
+            config,
+            backend: &backend,
+            clients,
+            sfn_client: config.clients.sfn(&backend),
+            recorder,
+        };
+        decisions.extend(
+            _evaluate_pipelines(
+                waiting_pipelines
+                    .iter()
+                    .filter_map(|maybe| maybe.as_ref().ok()),
+                context,
+                deadline,
+            )
+            .await,
+        );
+    }
+    failures.extend(decisions.iter().filter_map(|decision| {
+        decision.error.clone().map(|error| PipelineError {
+            pipeline_id: Some(String::from(&decision.pipeline_id)),
+            error,
+        })
+    }));
+    let deferred_ids: Vec<&str> = decisions
+        .iter()
+        .filter(|decision| decision.verdict == Verdict::Deferred)
+        .map(|decision| decision.pipeline_id.as_str())
+        .collect();
+    let mssg = format!(
+        "Reconciliation sweep evaluated {} waiting pipelines with {} failures.",
+        pipeline_count - deferred_ids.len(),
+        failures.len()
+    );
+    event!(Level::INFO, mssg);
+    if !deferred_ids.is_empty() {
+        // NOTE: deferred pipelines are still waiting, so the next sweep
+        // picks them up again.
+        let mssg = format!(
+            "Reconciliation sweep ran out of time; deferred pipelines {:?} to the next sweep.",
+            deferred_ids
+        );
+        event!(Level::WARN, mssg);
+    }
+    if failures.is_empty() {
+        Ok(decisions)
+    } else {
+        Err(PipelineEffectsError {
+            failures,
+            decisions,
+        })
+    }
+}
+
+async fn _evaluate_pipelines<'a>(
+    data_pipelines: impl Iterator<Item = &'a DataPipeline>,
+    context: EffectContext<'_>,
+    deadline: Option<SystemTime>,
+) -> Vec<PipelineDecision> {
+    // NOTE: buffered runs up to max_concurrency evaluations at once but
+    // yields decisions in the order of the pipelines. Each evaluation checks
+    // the deadline when it starts; evaluations already running may finish.
+    stream::iter(data_pipelines)
+        .map(|data_pipeline| async move {
+            if deadline.is_some_and(|deadline| SystemTime::now() >= deadline) {
+                let decision = PipelineDecision::deferred(data_pipeline);
+                decision.log();
+                return decision;
+            }
+            let decision = match maybe_trigger_pipeline(
+                data_pipeline,
+                context.backend,
+                context.clients,
+                context.sfn_client,
+                context.recorder,
+                context.config.refresh_expired_permits,
+            )
+            .await
+            {
+                Ok(decision) => decision,
+                Err(error) => PipelineDecision::failed(data_pipeline, error),
+            };
+            decision.log();
+            decision
+        })
+        .buffered(context.config.max_concurrency)
+        .collect()
+        .await
+}
+
+pub fn rest_model_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("PipelineRestModel", schema_for!(PipelineRestModel)),
+        ("DataSourceRestModel", schema_for!(DataSourceRestModel)),
+    ]
+}
@@ -0,0 +1,100 @@
+use super::{DataPipeline, ProcessingError};
+use serde::Serialize;
+use time::format_description::well_known::iso8601::Iso8601;
+use time::OffsetDateTime;
+use tracing::{event, Level};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Triggered,
+    Skipped,
+    Failed,
+    Deferred,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DependencyDecision {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_start: Option<String>,
+    pub newest_event_time: Option<String>,
+    // NOTE: None when the dependency was not evaluated, e.g. after a
+    // lenient rule already found a fresh dependency.
+    pub is_fresh: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PipelineDecision {
+    pub pipeline_id: String,
+    pub rule: String,
+    pub last_success_time: Option<String>,
+    pub dependencies: Vec<DependencyDecision>,
+    pub is_ready: Option<bool>,
+    pub verdict: Verdict,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ProcessingError>,
+}
+
+impl PipelineDecision {
+    pub fn new(data_pipeline: &DataPipeline) -> PipelineDecision {
+        PipelineDecision {
+            pipeline_id: String::from(&data_pipeline.id),
+            rule: data_pipeline.trigger_rule.describe(),
+            last_success_time: data_pipeline.last_success_time.map(format_time),
+            dependencies: data_pipeline
+                .dependency_urls
+                .iter()
+                .enumerate()
+                .map(|(index, url)| DependencyDecision {
+                    url: url.to_string(),
+                    window_start: data_pipeline
+                        .dependency_window_starts
+                        .get(index)
+                        .copied()
+                        .flatten()
+                        .map(format_time),
+                    newest_event_time: None,
+                    is_fresh: None,
+                })
+                .collect(),
+            is_ready: None,
+            verdict: Verdict::Skipped,
+            reason: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn failed(data_pipeline: &DataPipeline, error: ProcessingError) -> PipelineDecision {
+        PipelineDecision {
+            verdict: Verdict::Failed,
+            reason: format!("{:?}", error),
+            error: Some(error),
+            ..PipelineDecision::new(data_pipeline)
+        }
+    }
+
+    pub fn deferred(data_pipeline: &DataPipeline) -> PipelineDecision {
+        PipelineDecision {
+            verdict: Verdict::Deferred,
+            reason: String::from(
+                "Invocation deadline is too close to start evaluating this pipeline.",
+            ),
+            ..PipelineDecision::new(data_pipeline)
+        }
+    }
+
+    pub fn log(&self) {
+        let mssg = serde_json::to_string(self).unwrap_or(format!("{:?}", self));
+        match self.verdict {
+            Verdict::Failed => event!(Level::ERROR, mssg),
+            Verdict::Deferred => event!(Level::WARN, mssg),
+            _ => event!(Level::INFO, mssg),
+        }
+    }
+}
+
+pub fn format_time(time: OffsetDateTime) -> String {
+    time.format(&Iso8601::DEFAULT).unwrap_or(time.to_string())
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod test_endpoint;
-use super::ProcessingError;
+use super::{ProcessingError, RemoteFailure};
+use crate::config::Clients;
+use crate::routing::Backend;
 use crate::signing::get_signed_request_for_aws;
 use http;
 use reqwest::{self, Body, Client};
@@ -11,8 +13,8 @@ use url::Url;
 
 pub async fn fetch_rest_model<T1: DeserializeOwned, T2: AsRef<[u8]> + Into<Body>>(
     endpoint_url: &str,
-    aws_region: &str,
-    client: &Client,
+    backend: &Backend,
+    clients: &Clients,
     body: T2,
     headers: &HashMap<String, String>,
     method: &str,
@@ -22,22 +24,129 @@ pub async fn fetch_rest_model<T1: DeserializeOwned, T2: AsRef<[u8]> + Into<Body>
         headers,
         method,
         body,
-        aws_region,
+        &backend.aws_region,
+        clients.credentials(backend),
         "execute-api",
     )
     .await
     .map_err(|e| {
-        ProcessingError::ModelFetchFailure(format!("Failed to fetch data pipeline model:\n{:?}", e))
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
+            "Failed to fetch data pipeline model:\n{:?}",
+            e
+        )))
     })?;
-    let response_text = _fetch_response_text(http_request, client).await?;
+    let response_text = _fetch_response_text(http_request, &clients.http).await?;
     serde_json::from_str::<T1>(&response_text).map_err(|e| {
-        ProcessingError::ModelFetchFailure(format!(
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
             "Failed to deserialize response from remote api:\n{:?}\n{}",
             e, &response_text
-        ))
+        )))
     })
 }
 
+pub async fn fetch_optional_rest_model<T1: DeserializeOwned, T2: AsRef<[u8]> + Into<Body>>(
+    endpoint_url: &str,
+    backend: &Backend,
+    clients: &Clients,
+    body: T2,
+    headers: &HashMap<String, String>,
+    method: &str,
+) -> Result<Option<T1>, ProcessingError> {
+    let http_request = get_signed_request_for_aws(
+        endpoint_url,
+        headers,
+        method,
+        body,
+        &backend.aws_region,
+        clients.credentials(backend),
+        "execute-api",
+    )
+    .await
+    .map_err(|e| {
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
+            "Failed to fetch remote model:\n{:?}",
+            e
+        )))
+    })?;
+    let (status, response_text) =
+        _fetch_response_status_and_text(http_request, &clients.http).await?;
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    serde_json::from_str::<T1>(&response_text)
+        .map(Some)
+        .map_err(|e| {
+            ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
+                "Failed to deserialize response from remote api:\n{:?}\n{}",
+                e, &response_text
+            )))
+        })
+}
+
+pub async fn write_rest_model<T: AsRef<[u8]> + Into<Body>>(
+    endpoint_url: &str,
+    backend: &Backend,
+    clients: &Clients,
+    body: T,
+    headers: &HashMap<String, String>,
+    method: &str,
+) -> Result<(), ProcessingError> {
+    let http_request = get_signed_request_for_aws(
+        endpoint_url,
+        headers,
+        method,
+        body,
+        &backend.aws_region,
+        clients.credentials(backend),
+        "execute-api",
+    )
+    .await
+    .map_err(|e| {
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
+            "Failed to sign write request to remote api:\n{:?}",
+            e
+        )))
+    })?;
+    let (status, response_text) =
+        _fetch_response_status_and_text(http_request, &clients.http).await?;
+    _check_write_status(status, &response_text)
+}
+
+// NOTE: a conditional write is only applied while the precondition in its
+// body still holds; Ok(false) means the remote api rejected it as stale.
+pub async fn write_conditional_rest_model<T: AsRef<[u8]> + Into<Body>>(
+    endpoint_url: &str,
+    backend: &Backend,
+    clients: &Clients,
+    body: T,
+    headers: &HashMap<String, String>,
+    method: &str,
+) -> Result<bool, ProcessingError> {
+    let http_request = get_signed_request_for_aws(
+        endpoint_url,
+        headers,
+        method,
+        body,
+        &backend.aws_region,
+        clients.credentials(backend),
+        "execute-api",
+    )
+    .await
+    .map_err(|e| {
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
+            "Failed to sign write request to remote api:\n{:?}",
+            e
+        )))
+    })?;
+    let (status, response_text) =
+        _fetch_response_status_and_text(http_request, &clients.http).await?;
+    if status == reqwest::StatusCode::CONFLICT || status == reqwest::StatusCode::PRECONDITION_FAILED
+    {
+        return Ok(false);
+    }
+    _check_write_status(status, &response_text).map(|_| true)
+}
+
 pub fn basenames(paths: &Vec<String>) -> Vec<String> {
     paths.iter().map(|path| _basename(path)).collect()
 }
@@ -54,21 +163,61 @@ async fn _fetch_response_text<T: Into<Body>>(
     http_request: http::Request<T>,
     client: &Client,
 ) -> Result<String, ProcessingError> {
+    _fetch_response_status_and_text(http_request, client)
+        .await
+        .map(|(_, text)| text)
+}
+
+async fn _fetch_response_status_and_text<T: Into<Body>>(
+    http_request: http::Request<T>,
+    client: &Client,
+) -> Result<(reqwest::StatusCode, String), ProcessingError> {
     let request = reqwest::Request::try_from(http_request).map_err(|e| {
-        ProcessingError::ModelFetchFailure(format!("Failed to construct http request:\n{:?}", e))
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(format!(
+            "Failed to construct http request:\n{:?}",
+            e
+        )))
     })?;
     let response = client.execute(request).await.map_err(|e| {
-        ProcessingError::ModelFetchFailure(format!(
+        ProcessingError::ModelFetchFailure(RemoteFailure::transient(format!(
             "Failed to complete request to fetch remote model:\n{:?}",
             e
-        ))
+        )))
     })?;
-    response.text().await.map_err(|e| {
-        ProcessingError::ModelFetchFailure(format!(
+    let status = response.status();
+    let text = response.text().await.map_err(|e| {
+        ProcessingError::ModelFetchFailure(RemoteFailure::transient(format!(
             "Failed to convert response from remote api to text:\n{:?}",
             e
-        ))
-    })
+        )))
+    })?;
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ProcessingError::ModelFetchFailure(
+            RemoteFailure::transient(format!(
+                "Remote api responded with status {}:\n{}",
+                status, text
+            )),
+        ));
+    }
+    Ok((status, text))
+}
+
+fn _check_write_status(
+    status: reqwest::StatusCode,
+    response_text: &str,
+) -> Result<(), ProcessingError> {
+    // NOTE: writes only look at the status; a 201 or 204 may have no body
+    // at all. Transient statuses were already rejected while fetching.
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(ProcessingError::ModelFetchFailure(RemoteFailure::terminal(
+            format!(
+                "Remote api rejected write with status {}:\n{}",
+                status, response_text
+            ),
+        )))
+    }
 }
 
 // TODO: add ability to specify query params
@@ -1,4 +1,4 @@
-use super::{basenames, construct_endpoint_url};
+use super::{_check_write_status, basenames, construct_endpoint_url};
 use rand;
 
 #[test]
@@ -167,3 +167,27 @@ fn test_basenames_test_mixed_case() {
         assert_eq!(&base_names[i], base_name);
     }
 }
+
+#[test]
+fn test_check_write_status_test_success_without_body() {
+    assert_eq!(
+        Ok(()),
+        _check_write_status(reqwest::StatusCode::NO_CONTENT, "")
+    );
+    assert_eq!(
+        Ok(()),
+        _check_write_status(reqwest::StatusCode::CREATED, "")
+    );
+}
+
+#[test]
+fn test_check_write_status_test_client_errors_are_terminal() {
+    for status in [
+        reqwest::StatusCode::BAD_REQUEST,
+        reqwest::StatusCode::FORBIDDEN,
+        reqwest::StatusCode::CONFLICT,
+    ] {
+        let error = _check_write_status(status, r#"{"message":"Forbidden"}"#).unwrap_err();
+        assert!(!error.is_retryable());
+    }
+}
@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod test_expressions;
+
+use super::ProcessingError;
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExpressionNode {
+    Dependency(usize),
+    And(Box<ExpressionNode>, Box<ExpressionNode>),
+    Or(Box<ExpressionNode>, Box<ExpressionNode>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyExpression {
+    source: String,
+    root: ExpressionNode,
+}
+
+impl DependencyExpression {
+    pub fn parse(
+        source: &str,
+        dependencies: &[String],
+    ) -> Result<DependencyExpression, ProcessingError> {
+        let mut parser = Parser {
+            tokens: _tokenize(source),
+            position: 0,
+            source_length: source.len(),
+            dependencies,
+        };
+        let root = parser
+            ._parse_or()
+            .and_then(|root| match parser._peek() {
+                None => Ok(root),
+                Some((token, at)) => Err(format!(
+                    "unexpected {} at position {}; expected AND, OR or the end of the expression",
+                    _describe(token),
+                    at
+                )),
+            })
+            .map_err(|e| {
+                ProcessingError::InvalidDependencyExpression(format!(
+                    "Failed to parse dependency expression {:?}: {}.",
+                    source, e
+                ))
+            })?;
+        Ok(DependencyExpression {
+            source: String::from(source),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // NOTE: evaluates with three-valued logic where None is a dependency
+    // that was not fetched yet, so the result is known as soon as it is
+    // Some, regardless of the remaining dependencies.
+    pub fn evaluate(&self, freshness: &[Option<bool>]) -> Option<bool> {
+        _evaluate_node(&self.root, freshness)
+    }
+}
+
+fn _evaluate_node(node: &ExpressionNode, freshness: &[Option<bool>]) -> Option<bool> {
+    match node {
+        ExpressionNode::Dependency(index) => freshness.get(*index).copied().flatten(),
+        ExpressionNode::And(left, right) => {
+            match (
+                _evaluate_node(left, freshness),
+                _evaluate_node(right, freshness),
+            ) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            }
+        }
+        ExpressionNode::Or(left, right) => {
+            match (
+                _evaluate_node(left, freshness),
+                _evaluate_node(right, freshness),
+            ) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn _tokenize(source: &str) -> Vec<(Token, usize)> {
+    let mut tokens = vec![];
+    let mut name_start: Option<usize> = None;
+    for (at, character) in source.char_indices() {
+        let is_delimiter = character.is_whitespace() || character == '(' || character == ')';
+        if !is_delimiter {
+            name_start.get_or_insert(at);
+            continue;
+        }
+        if let Some(start) = name_start.take() {
+            tokens.push((_name_token(&source[start..at]), start));
+        }
+        match character {
+            '(' => tokens.push((Token::LeftParen, at)),
+            ')' => tokens.push((Token::RightParen, at)),
+            _ => {}
+        }
+    }
+    if let Some(start) = name_start {
+        tokens.push((_name_token(&source[start..]), start));
+    }
+    tokens
+}
+
+fn _name_token(name: &str) -> Token {
+    match name {
+        "AND" => Token::And,
+        "OR" => Token::Or,
+        _ => Token::Name(String::from(name)),
+    }
+}
+
+fn _describe(token: &Token) -> String {
+    match token {
+        Token::LeftParen => String::from("'('"),
+        Token::RightParen => String::from("')'"),
+        Token::And => String::from("AND"),
+        Token::Or => String::from("OR"),
+        Token::Name(name) => format!("dependency {:?}", name),
+    }
+}
+
+fn _alias(dependency: &str) -> &str {
+    dependency
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(dependency)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    source_length: usize,
+    dependencies: &'a [String],
+}
+
+impl Parser<'_> {
+    fn _peek(&self) -> Option<(&Token, usize)> {
+        self.tokens
+            .get(self.position)
+            .map(|(token, at)| (token, *at))
+    }
+
+    fn _parse_or(&mut self) -> Result<ExpressionNode, String> {
+        let mut node = self._parse_and()?;
+        while let Some((Token::Or, _)) = self._peek() {
+            self.position += 1;
+            node = ExpressionNode::Or(Box::new(node), Box::new(self._parse_and()?));
+        }
+        Ok(node)
+    }
+
+    fn _parse_and(&mut self) -> Result<ExpressionNode, String> {
+        let mut node = self._parse_operand()?;
+        while let Some((Token::And, _)) = self._peek() {
+            self.position += 1;
+            node = ExpressionNode::And(Box::new(node), Box::new(self._parse_operand()?));
+        }
+        Ok(node)
+    }
+
+    fn _parse_operand(&mut self) -> Result<ExpressionNode, String> {
+        let (token, at) = match self._peek() {
+            Some((token, at)) => (token.clone(), at),
+            None => {
+                return Err(format!(
+                    "unexpected end of expression at position {}; expected a dependency or '('",
+                    self.source_length
+                ))
+            }
+        };
+        self.position += 1;
+        match token {
+            Token::Name(name) => self._resolve(&name, at).map(ExpressionNode::Dependency),
+            Token::LeftParen => {
+                let node = self._parse_or()?;
+                match self._peek() {
+                    Some((Token::RightParen, _)) => {
+                        self.position += 1;
+                        Ok(node)
+                    }
+                    Some((token, closing_at)) => Err(format!(
+                        "unexpected {} at position {}; expected ')' to close '(' at position {}",
+                        _describe(token),
+                        closing_at,
+                        at
+                    )),
+                    None => Err(format!(
+                        "unexpected end of expression at position {}; expected ')' to close '(' at position {}",
+                        self.source_length, at
+                    )),
+                }
+            }
+            token => Err(format!(
+                "unexpected {} at position {}; expected a dependency or '('",
+                _describe(&token),
+                at
+            )),
+        }
+    }
+
+    fn _resolve(&self, name: &str, at: usize) -> Result<usize, String> {
+        if let Some(index) = self
+            .dependencies
+            .iter()
+            .position(|dependency| dependency == name)
+        {
+            return Ok(index);
+        }
+        let matches: Vec<usize> = self
+            .dependencies
+            .iter()
+            .enumerate()
+            .filter(|(_, dependency)| _alias(dependency) == name)
+            .map(|(index, _)| index)
+            .collect();
+        match matches.as_slice() {
+            [index] => Ok(*index),
+            [] => Err(format!(
+                "unknown dependency {:?} at position {}; expected a dependency url or one of the aliases {:?}",
+                name,
+                at,
+                self.dependencies
+                    .iter()
+                    .map(|dependency| _alias(dependency))
+                    .collect::<Vec<&str>>()
+            )),
+            _ => Err(format!(
+                "ambiguous alias {:?} at position {} matches {} dependencies; use the full url",
+                name,
+                at,
+                matches.len()
+            )),
+        }
+    }
+}
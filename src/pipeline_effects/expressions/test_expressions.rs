@@ -0,0 +1,114 @@
+use super::*;
+
+fn _dependencies() -> Vec<String> {
+    vec![
+        String::from("https://api.hotpotato.com/v1/data-sources/orders"),
+        String::from("https://api.hotpotato.com/v1/data-sources/customers"),
+        String::from("https://api.hotpotato.com/v1/data-sources/full_snapshot"),
+    ]
+}
+
+fn _parse_error(source: &str) -> String {
+    match DependencyExpression::parse(source, &_dependencies()) {
+        Err(ProcessingError::InvalidDependencyExpression(s)) => s,
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn dependency_expression_test_parses_aliases_and_precedence() {
+    let expression =
+        DependencyExpression::parse("orders AND customers OR full_snapshot", &_dependencies())
+            .unwrap();
+    assert_eq!(expression.source(), "orders AND customers OR full_snapshot");
+    assert_eq!(
+        expression.root,
+        ExpressionNode::Or(
+            Box::new(ExpressionNode::And(
+                Box::new(ExpressionNode::Dependency(0)),
+                Box::new(ExpressionNode::Dependency(1))
+            )),
+            Box::new(ExpressionNode::Dependency(2))
+        )
+    );
+}
+
+#[test]
+fn dependency_expression_test_parses_urls_and_parentheses() {
+    let expression = DependencyExpression::parse(
+        "orders AND (https://api.hotpotato.com/v1/data-sources/customers OR full_snapshot)",
+        &_dependencies(),
+    )
+    .unwrap();
+    assert_eq!(
+        expression.root,
+        ExpressionNode::And(
+            Box::new(ExpressionNode::Dependency(0)),
+            Box::new(ExpressionNode::Or(
+                Box::new(ExpressionNode::Dependency(1)),
+                Box::new(ExpressionNode::Dependency(2))
+            ))
+        )
+    );
+}
+
+#[test]
+fn dependency_expression_test_evaluates_with_unknown_dependencies() {
+    let expression =
+        DependencyExpression::parse("(orders AND customers) OR full_snapshot", &_dependencies())
+            .unwrap();
+    assert_eq!(expression.evaluate(&[None, None, None]), None);
+    assert_eq!(expression.evaluate(&[Some(false), None, None]), None);
+    assert_eq!(
+        expression.evaluate(&[Some(false), None, Some(false)]),
+        Some(false)
+    );
+    assert_eq!(
+        expression.evaluate(&[Some(true), Some(true), None]),
+        Some(true)
+    );
+    assert_eq!(expression.evaluate(&[None, None, Some(true)]), Some(true));
+}
+
+#[test]
+fn dependency_expression_test_parse_errors() {
+    assert_eq!(
+        _parse_error("orders AND"),
+        "Failed to parse dependency expression \"orders AND\": unexpected end of expression at position 10; expected a dependency or '('."
+    );
+    assert_eq!(
+        _parse_error("(orders OR customers"),
+        "Failed to parse dependency expression \"(orders OR customers\": unexpected end of expression at position 20; expected ')' to close '(' at position 0."
+    );
+    assert_eq!(
+        _parse_error("orders customers"),
+        "Failed to parse dependency expression \"orders customers\": unexpected dependency \"customers\" at position 7; expected AND, OR or the end of the expression."
+    );
+    assert_eq!(
+        _parse_error("OR orders"),
+        "Failed to parse dependency expression \"OR orders\": unexpected OR at position 0; expected a dependency or '('."
+    );
+    assert_eq!(
+        _parse_error("orders AND invoices"),
+        "Failed to parse dependency expression \"orders AND invoices\": unknown dependency \"invoices\" at position 11; expected a dependency url or one of the aliases [\"orders\", \"customers\", \"full_snapshot\"]."
+    );
+}
+
+#[test]
+fn dependency_expression_test_ambiguous_alias() {
+    let dependencies = vec![
+        String::from("https://eu.hotpotato.com/v1/data-sources/orders"),
+        String::from("https://us.hotpotato.com/v1/data-sources/orders"),
+    ];
+    match DependencyExpression::parse("orders", &dependencies) {
+        Err(ProcessingError::InvalidDependencyExpression(s)) => assert!(s.contains(
+            "ambiguous alias \"orders\" at position 0 matches 2 dependencies; use the full url"
+        )),
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+    assert!(DependencyExpression::parse(
+        "https://eu.hotpotato.com/v1/data-sources/orders",
+        &dependencies
+    )
+    .is_ok());
+}
@@ -0,0 +1,140 @@
+pub mod remote;
+#[cfg(test)]
+mod test_failures;
+
+use super::{
+    gathering::{
+        get_failure_policy, get_max_retries, remote::fetch_pipeline_model, PipelineRestModel,
+    },
+    EffectContext, FailurePolicy, ProcessingError,
+};
+use crate::config::Config;
+use crate::dry_run::PlannedEffect;
+use crate::entities::{PipelineFailure, PipelineFailureStatus};
+use remote::{fetch_consecutive_failure_count, mark_event_as_blocking, start_pipeline_execution};
+use std::future::Future;
+use tracing::{event, Level};
+
+async fn handle_pipeline_failure<F, FutF, C, FutC, G, FutG, H, FutH>(
+    pipeline_id: &str,
+    failure: &PipelineFailure,
+    config: &Config,
+    fn_fetch_data_pipeline: F,
+    fn_count_consecutive_failures: C,
+    fn_retry_pipeline: G,
+    fn_block_downstream: H,
+) -> Result<FailurePolicy, ProcessingError>
+where
+    F: Fn(String) -> FutF,
+    FutF: Future<Output = Result<PipelineRestModel, ProcessingError>>,
+    C: Fn(String) -> FutC,
+    FutC: Future<Output = Result<usize, ProcessingError>>,
+    G: Fn(String, Option<String>) -> FutG,
+    FutG: Future<Output = Result<(), ProcessingError>>,
+    H: Fn(String) -> FutH,
+    FutH: Future<Output = Result<(), ProcessingError>>,
+{
+    let pipeline_model = fn_fetch_data_pipeline(String::from(pipeline_id)).await?;
+    let mut policy = get_failure_policy(&pipeline_model, &config.default_failure_policy)?;
+    // NOTE: an aborted execution was stopped on purpose, e.g. by an operator,
+    // so it is only reported instead of started again.
+    if policy == FailurePolicy::Retry && failure.status == PipelineFailureStatus::Aborted {
+        policy = FailurePolicy::Notify;
+    }
+    if policy == FailurePolicy::Retry {
+        // NOTE: the failure being handled is already persisted, so it is part
+        // of the count; every earlier one in the streak was already retried.
+        let max_retries = get_max_retries(&pipeline_model, config.max_pipeline_retries);
+        let failure_count = fn_count_consecutive_failures(String::from(pipeline_id)).await?;
+        if failure_count > max_retries as usize {
+            let mssg = format!(
+                "Pipeline with id {} failed {} times in a row, exhausting its {} retries; blocking downstream pipelines instead.",
+                pipeline_id, failure_count, max_retries
+            );
+            event!(Level::ERROR, mssg);
+            policy = FailurePolicy::Block;
+        }
+    }
+    let mssg = format!(
+        "Pipeline with id {} ended with status {:?} (error: {:?}, cause: {:?}); applying failure policy {:?}.",
+        pipeline_id, failure.status, failure.error, failure.cause, policy
+    );
+    event!(Level::ERROR, mssg);
+    match policy {
+        FailurePolicy::Retry => {
+            let state_machine_arn = failure.state_machine_arn.clone().ok_or(
+                ProcessingError::PipelineStateMachineMissing(format!(
+                    "Cannot retry pipeline with id {} without its state machine arn.",
+                    pipeline_id
+                )),
+            )?;
+            fn_retry_pipeline(state_machine_arn, failure.execution_input.clone()).await?;
+        }
+        FailurePolicy::Notify => {}
+        FailurePolicy::Block => {
+            fn_block_downstream(String::from(pipeline_id)).await?;
+        }
+    };
+    Ok(policy)
+}
+
+pub async fn apply_failure_policy(
+    event_id: &str,
+    pipeline_id: &str,
+    failure: &PipelineFailure,
+    context: EffectContext<'_>,
+) -> Result<(), ProcessingError> {
+    let EffectContext {
+        config,
+        backend,
+        clients,
+        sfn_client,
+        recorder,
+    } = context;
+    let fn_fetch_data_pipeline = |pipeline_id: String| async move {
+        fetch_pipeline_model(&pipeline_id, backend, clients).await
+    };
+    let fn_retry_pipeline = |state_machine_arn: String, input: Option<String>| async move {
+        match recorder {
+            Some(recorder) => {
+                recorder.record(
+                    pipeline_id,
+                    PlannedEffect::StartExecution {
+                        state_machine_arn,
+                        input,
+                    },
+                );
+                Ok(())
+            }
+            None => start_pipeline_execution(&state_machine_arn, input, sfn_client).await,
+        }
+    };
+    let fn_count_consecutive_failures = |pipeline_id: String| async move {
+        fetch_consecutive_failure_count(&pipeline_id, backend, clients).await
+    };
+    let fn_block_downstream = |pipeline_id: String| async move {
+        match recorder {
+            Some(recorder) => {
+                recorder.record(
+                    &pipeline_id,
+                    PlannedEffect::MarkEventAsBlocking {
+                        event_id: String::from(event_id),
+                    },
+                );
+                Ok(())
+            }
+            None => mark_event_as_blocking(&pipeline_id, event_id, backend, clients).await,
+        }
+    };
+    handle_pipeline_failure(
+        pipeline_id,
+        failure,
+        config,
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await?;
+    Ok(())
+}
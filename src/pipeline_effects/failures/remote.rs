@@ -0,0 +1,105 @@
+use super::ProcessingError;
+use crate::config::Clients;
+use crate::events::remote::{EventRestModel, EventType};
+use crate::pipeline_effects::endpoint::{
+    construct_endpoint_url, fetch_rest_model, write_rest_model,
+};
+use crate::pipeline_effects::{is_transient_sdk_error, RemoteFailure};
+use crate::routing::Backend;
+use aws_sdk_sfn;
+use std::collections::HashMap;
+
+pub async fn start_pipeline_execution(
+    state_machine_arn: &str,
+    input: Option<String>,
+    client: &aws_sdk_sfn::Client,
+) -> Result<(), ProcessingError> {
+    let result_future = client
+        .start_execution()
+        .state_machine_arn(state_machine_arn)
+        .set_input(input)
+        .send();
+    match result_future.await {
+        Ok(_) => Ok(()),
+        Err(error) if is_transient_sdk_error(&error) => Err(ProcessingError::PipelineRetryError(
+            RemoteFailure::transient(format!(
+                "Failed to start a new execution of state machine {}.\n{:?}",
+                state_machine_arn, error
+            )),
+        )),
+        Err(error) => Err(ProcessingError::PipelineRetryError(
+            RemoteFailure::terminal(format!(
+                "Failed to start a new execution of state machine {}.\n{:?}",
+                state_machine_arn, error
+            )),
+        )),
+    }
+}
+
+pub async fn mark_event_as_blocking(
+    pipeline_id: &str,
+    event_id: &str,
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<(), ProcessingError> {
+    let endpoint_url = construct_endpoint_url(
+        &backend.endpoint_prefix,
+        &vec![
+            String::from("pipelines"),
+            String::from(pipeline_id),
+            String::from("events"),
+            String::from(event_id),
+        ],
+    )?;
+    let headers = HashMap::from([(
+        String::from("Content-Type"),
+        String::from("application/json"),
+    )]);
+    write_rest_model(
+        endpoint_url.as_str(),
+        backend,
+        clients,
+        serde_json::json!({ "blocks_downstream": true }).to_string(),
+        &headers,
+        "PATCH",
+    )
+    .await
+}
+
+pub async fn fetch_consecutive_failure_count(
+    pipeline_id: &str,
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<usize, ProcessingError> {
+    let endpoint_url = construct_endpoint_url(
+        &backend.endpoint_prefix,
+        &vec![
+            String::from("pipelines"),
+            String::from(pipeline_id),
+            String::from("events"),
+        ],
+    )?;
+    let mut endpoint_url = url::Url::parse(&endpoint_url).map_err(|e| {
+        ProcessingError::UrlParseFailure(format!(
+            "Failed to parse pipeline events url {}:\n{:?}",
+            endpoint_url, e
+        ))
+    })?;
+    endpoint_url
+        .query_pairs_mut()
+        .clear()
+        .append_pair("descending_order", "true");
+    let event_models = fetch_rest_model::<Vec<EventRestModel>, String>(
+        endpoint_url.as_str(),
+        backend,
+        clients,
+        String::from(""),
+        &HashMap::<String, String>::new(),
+        "GET",
+    )
+    .await?;
+    Ok(event_models
+        .iter()
+        .take_while(|event| event.event_type == EventType::DataPipelineFailure)
+        .count())
+}
@@ -0,0 +1,241 @@
+use super::*;
+use crate::config::test_config::build_sdk_config;
+use crate::entities::PipelineFailureStatus;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+fn _build_config() -> Config {
+    let env_vars = HashMap::from([
+        (
+            String::from("ENV_ENDPOINT_URL"),
+            String::from("http://127.0.0.1:9/"),
+        ),
+        (String::from("ENV_AWS_REGION"), String::from("eu-west-1")),
+    ]);
+    Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap()
+}
+
+fn _build_pipeline_model(pipeline_id: &str, failure_policy: Option<&str>) -> PipelineRestModel {
+    serde_json::from_value(json!({
+        "id": pipeline_id,
+        "description": format!("pipeline: {}", pipeline_id),
+        "last_success_time": null,
+        "source_dependencies": [],
+        "trigger_rule": "LENIENT",
+        "callback_token": null,
+        "failure_policy": failure_policy,
+        "max_retries": 2
+    }))
+    .unwrap()
+}
+
+fn _build_failure(state_machine_arn: Option<String>) -> PipelineFailure {
+    PipelineFailure {
+        status: PipelineFailureStatus::Failed,
+        error: Some(String::from("States.TaskFailed")),
+        cause: Some(String::from("boom")),
+        state_machine_arn,
+        execution_input: Some(String::from("{\"foo\": 1}")),
+    }
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_retry_policy_starts_new_execution() {
+    let pipeline_id = format!("pipeline{}", rand::random::<u32>());
+    let state_machine_arn = format!(
+        "arn:aws:states:us-east-1:123456789012:stateMachine:{}",
+        &pipeline_id
+    );
+    let failure = _build_failure(Some(state_machine_arn.to_string()));
+    let retried = Mutex::new(vec![]);
+    let fn_fetch_data_pipeline =
+        |id: String| async move { Ok(_build_pipeline_model(&id, Some("RETRY"))) };
+    let fn_count_consecutive_failures = |_: String| async move { Ok(1) };
+    let fn_retry_pipeline = |arn: String, input: Option<String>| {
+        retried.lock().unwrap().push((arn, input));
+        async move { Ok(()) }
+    };
+    let fn_block_downstream = |_: String| async move {
+        panic!("retry policy must not block downstream pipelines");
+    };
+    let result = handle_pipeline_failure(
+        &pipeline_id,
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert_eq!(result.unwrap(), FailurePolicy::Retry);
+    assert_eq!(
+        retried.into_inner().unwrap(),
+        vec![(state_machine_arn, Some(String::from("{\"foo\": 1}")))]
+    );
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_retry_policy_does_not_restart_aborted_execution() {
+    let failure = PipelineFailure {
+        status: PipelineFailureStatus::Aborted,
+        .._build_failure(Some(String::from(
+            "arn:aws:states:us-east-1:123456789012:stateMachine:pipeline",
+        )))
+    };
+    let fn_fetch_data_pipeline =
+        |id: String| async move { Ok(_build_pipeline_model(&id, Some("RETRY"))) };
+    let fn_count_consecutive_failures = |_: String| async move {
+        panic!("an aborted execution must not count against the retry budget");
+    };
+    let fn_retry_pipeline = |_: String, _: Option<String>| async move {
+        panic!("an aborted execution must not be restarted");
+    };
+    let fn_block_downstream = |_: String| async move {
+        panic!("an aborted execution must not block downstream pipelines");
+    };
+    let result = handle_pipeline_failure(
+        "pipeline1",
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert_eq!(result.unwrap(), FailurePolicy::Notify);
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_retry_policy_without_state_machine_arn() {
+    let failure = _build_failure(None);
+    let fn_fetch_data_pipeline =
+        |id: String| async move { Ok(_build_pipeline_model(&id, Some("RETRY"))) };
+    let fn_count_consecutive_failures = |_: String| async move { Ok(1) };
+    let fn_retry_pipeline = |_: String, _: Option<String>| async move { Ok(()) };
+    let fn_block_downstream = |_: String| async move { Ok(()) };
+    let result = handle_pipeline_failure(
+        "pipeline1",
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProcessingError::PipelineStateMachineMissing(_))
+    ));
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_retry_budget_exhausted_blocks_downstream() {
+    let pipeline_id = format!("pipeline{}", rand::random::<u32>());
+    let failure = _build_failure(Some(String::from(
+        "arn:aws:states:us-east-1:123456789012:stateMachine:pipeline",
+    )));
+    let blocked = Mutex::new(vec![]);
+    let fn_fetch_data_pipeline =
+        |id: String| async move { Ok(_build_pipeline_model(&id, Some("RETRY"))) };
+    let fn_count_consecutive_failures = |_: String| async move { Ok(3) };
+    let fn_retry_pipeline = |_: String, _: Option<String>| async move {
+        panic!("an exhausted retry budget must not retry the pipeline");
+    };
+    let fn_block_downstream = |pipeline_id: String| {
+        blocked.lock().unwrap().push(pipeline_id);
+        async move { Ok(()) }
+    };
+    let result = handle_pipeline_failure(
+        &pipeline_id,
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert_eq!(result.unwrap(), FailurePolicy::Block);
+    assert_eq!(blocked.into_inner().unwrap(), vec![pipeline_id]);
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_notify_is_the_default_policy() {
+    let failure = _build_failure(None);
+    let fn_fetch_data_pipeline = |id: String| async move { Ok(_build_pipeline_model(&id, None)) };
+    let fn_count_consecutive_failures = |_: String| async move { Ok(1) };
+    let fn_retry_pipeline = |_: String, _: Option<String>| async move {
+        panic!("notify policy must not retry the pipeline");
+    };
+    let fn_block_downstream = |_: String| async move {
+        panic!("notify policy must not block downstream pipelines");
+    };
+    let result = handle_pipeline_failure(
+        "pipeline1",
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert_eq!(result.unwrap(), FailurePolicy::Notify);
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_block_policy_marks_event() {
+    let pipeline_id = format!("pipeline{}", rand::random::<u32>());
+    let failure = _build_failure(None);
+    let blocked = Mutex::new(vec![]);
+    let fn_fetch_data_pipeline =
+        |id: String| async move { Ok(_build_pipeline_model(&id, Some("BLOCK"))) };
+    let fn_count_consecutive_failures = |_: String| async move { Ok(1) };
+    let fn_retry_pipeline = |_: String, _: Option<String>| async move {
+        panic!("block policy must not retry the pipeline");
+    };
+    let fn_block_downstream = |pipeline_id: String| {
+        blocked.lock().unwrap().push(pipeline_id);
+        async move { Ok(()) }
+    };
+    let result = handle_pipeline_failure(
+        &pipeline_id,
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert_eq!(result.unwrap(), FailurePolicy::Block);
+    assert_eq!(blocked.into_inner().unwrap(), vec![pipeline_id]);
+}
+
+#[tokio::test]
+async fn handle_pipeline_failure_test_unrecognized_policy() {
+    let failure = _build_failure(None);
+    let fn_fetch_data_pipeline =
+        |id: String| async move { Ok(_build_pipeline_model(&id, Some("IGNORE"))) };
+    let fn_count_consecutive_failures = |_: String| async move { Ok(1) };
+    let fn_retry_pipeline = |_: String, _: Option<String>| async move { Ok(()) };
+    let fn_block_downstream = |_: String| async move { Ok(()) };
+    let result = handle_pipeline_failure(
+        "pipeline1",
+        &failure,
+        &_build_config(),
+        fn_fetch_data_pipeline,
+        fn_count_consecutive_failures,
+        fn_retry_pipeline,
+        fn_block_downstream,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProcessingError::UnrecognizedFailurePolicy(_))
+    ));
+}
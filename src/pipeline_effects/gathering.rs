@@ -4,22 +4,28 @@ mod test_gathering;
 
 use super::{
     endpoint::{basenames, construct_endpoint_url, fetch_rest_model},
-    DataPipeline, PipelineTriggerPermit, ProcessingError, TriggerPermitType,
+    rules::{TriggerRule, TriggerRuleParameters, TriggerRuleRegistry},
+    DataPipeline, FailurePolicy, PipelineError, PipelineTriggerPermit, ProcessingError,
 };
+use crate::config::Clients;
 use crate::entities::EventPayload;
-use remote::{fetch_data_source_model, fetch_pipeline_model};
-use reqwest::Client;
+use crate::routing::Backend;
+use futures::stream::{self, StreamExt};
+use remote::{fetch_all_pipeline_models, fetch_data_source_model, fetch_pipeline_model};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use std::future::Future;
 use time::format_description::well_known::iso8601::Iso8601;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 use url::Url;
 
 async fn gather_data_pipelines<F, FutF, G, FutG>(
     event_payload: EventPayload,
     fn_fetch_data_source: F,
     fn_fetch_data_pipeline: G,
-) -> Result<Vec<DataPipeline>, ProcessingError>
+    trigger_rules: &TriggerRuleRegistry,
+    concurrency_limit: usize,
+) -> Result<Vec<Result<DataPipeline, PipelineError>>, ProcessingError>
 where
     F: Fn(String) -> FutF,
     FutF: Future<Output = Result<DataSourceRestModel, ProcessingError>>,
@@ -30,19 +36,30 @@ where
         EventPayload::DataSource { id } => {
             let source_id = id.to_string();
             let data_source_rest_model = fn_fetch_data_source(source_id).await?;
-            let mut pipelines = Vec::new();
-            for pipeline_id in data_source_rest_model.dependent_pipelines {
-                let pipeline_model = fn_fetch_data_pipeline(String::from(pipeline_id)).await?;
-                pipelines.push(DataPipeline {
-                    permit: _get_trigger_permit(&pipeline_model)?,
-                    id: String::from(&pipeline_model.id),
-                    description: String::from(&pipeline_model.description),
-                    last_success_time: _map_to_offsetdatetime(&pipeline_model.last_success_time)?,
-                    dependency_urls: _map_str_to_url(&pipeline_model.source_dependencies)?,
+            // NOTE: buffered keeps the order of the dependent pipelines, so
+            // each result lines up with the pipeline id it was fetched for.
+            // A pipeline that fails to fetch or build does not stop the
+            // evaluation of its siblings.
+            let pipeline_ids = data_source_rest_model.dependent_pipelines;
+            let maybe_pipeline_models = stream::iter(pipeline_ids.clone())
+                .map(&fn_fetch_data_pipeline)
+                .buffered(concurrency_limit)
+                .collect::<Vec<Result<PipelineRestModel, ProcessingError>>>()
+                .await;
+            Ok(pipeline_ids
+                .into_iter()
+                .zip(maybe_pipeline_models)
+                .map(|(pipeline_id, maybe_pipeline_model)| {
+                    maybe_pipeline_model
+                        .and_then(|pipeline_model| {
+                            _build_data_pipeline(&pipeline_model, trigger_rules)
+                        })
+                        .map_err(|error| PipelineError {
+                            pipeline_id: Some(pipeline_id),
+                            error,
+                        })
                 })
-            }
-            let pipelines = pipelines;
-            Ok(pipelines)
+                .collect())
         }
         EventPayload::DataPipeline {
             id,
@@ -53,20 +70,24 @@ where
             let maybe_fetched_dt = _map_to_offsetdatetime(&pipeline_model.last_success_time)?;
             if let Some(fetched_dt) = maybe_fetched_dt {
                 if fetched_dt == success_time {
-                    let permit = _get_trigger_permit(&pipeline_model)?;
-                    if let TriggerPermitType::Lenient(Some(fetched_permit))
-                    | TriggerPermitType::Strict(Some(fetched_permit)) = &permit
-                    {
+                    let trigger_rule = _get_trigger_rule(&pipeline_model, trigger_rules)?;
+                    let permit = _get_trigger_permit(&pipeline_model, &OffsetDateTime::now_utc())?;
+                    if let Some(fetched_permit) = &permit {
                         if callback_token.eq(&fetched_permit.content) {
-                            Ok(vec![DataPipeline {
+                            Ok(vec![Ok(DataPipeline {
                                 permit: permit,
+                                trigger_rule,
+                                dependency_urls: _map_str_to_url(&_dependency_url_strings(
+                                    &pipeline_model,
+                                ))?,
+                                dependency_window_starts: _map_to_window_starts(
+                                    &pipeline_model.source_dependencies,
+                                    &OffsetDateTime::now_utc(),
+                                ),
                                 id: pipeline_model.id,
                                 description: pipeline_model.description,
                                 last_success_time: maybe_fetched_dt,
-                                dependency_urls: _map_str_to_url(
-                                    &pipeline_model.source_dependencies,
-                                )?,
-                            }])
+                            })])
                         } else {
                             Err(ProcessingError::PermitContentConflict(String::from(
                                 "Conflict between provided permit content and stored permit content."
@@ -88,9 +109,53 @@ where
                 )))
             }
         }
+        // NOTE: failure events are handled by the pipeline's failure policy
+        // and never make any pipeline a candidate for triggering.
+        EventPayload::DataPipelineFailure { .. } => Ok(vec![]),
     }
 }
 
+async fn gather_waiting_data_pipelines<F, FutF>(
+    fn_fetch_all_pipelines: F,
+    trigger_rules: &TriggerRuleRegistry,
+) -> Result<Vec<Result<DataPipeline, PipelineError>>, ProcessingError>
+where
+    F: Fn() -> FutF,
+    FutF: Future<Output = Result<Vec<PipelineRestModel>, ProcessingError>>,
+{
+    let pipeline_models = fn_fetch_all_pipelines().await?;
+    // NOTE: only pipelines holding a callback token are waiting to be
+    // triggered; everything else is either running or never started.
+    Ok(pipeline_models
+        .iter()
+        .filter(|pipeline_model| pipeline_model.callback_token.is_some())
+        .map(|pipeline_model| {
+            _build_data_pipeline(pipeline_model, trigger_rules).map_err(|error| PipelineError {
+                pipeline_id: Some(String::from(&pipeline_model.id)),
+                error,
+            })
+        })
+        .collect())
+}
+
+fn _build_data_pipeline(
+    pipeline_model: &PipelineRestModel,
+    trigger_rules: &TriggerRuleRegistry,
+) -> Result<DataPipeline, ProcessingError> {
+    Ok(DataPipeline {
+        permit: _get_trigger_permit(pipeline_model, &OffsetDateTime::now_utc())?,
+        trigger_rule: _get_trigger_rule(pipeline_model, trigger_rules)?,
+        id: String::from(&pipeline_model.id),
+        description: String::from(&pipeline_model.description),
+        last_success_time: _map_to_offsetdatetime(&pipeline_model.last_success_time)?,
+        dependency_urls: _map_str_to_url(&_dependency_url_strings(pipeline_model))?,
+        dependency_window_starts: _map_to_window_starts(
+            &pipeline_model.source_dependencies,
+            &OffsetDateTime::now_utc(),
+        ),
+    })
+}
+
 fn _map_str_to_url(url_strings: &Vec<String>) -> Result<Vec<Url>, ProcessingError> {
     let mut urls: Vec<Url> = Vec::new();
     for url_string in url_strings {
@@ -107,6 +172,32 @@ fn _map_str_to_url(url_strings: &Vec<String>) -> Result<Vec<Url>, ProcessingErro
     Ok(urls)
 }
 
+fn _dependency_url_strings(pipeline_model: &PipelineRestModel) -> Vec<String> {
+    pipeline_model
+        .source_dependencies
+        .iter()
+        .map(|dependency| String::from(dependency.url()))
+        .collect()
+}
+
+fn _map_to_window_starts(
+    source_dependencies: &[SourceDependency],
+    now: &OffsetDateTime,
+) -> Vec<Option<OffsetDateTime>> {
+    source_dependencies
+        .iter()
+        .map(|dependency| match dependency {
+            SourceDependency::Url(_) => None,
+            SourceDependency::Windowed { max_age_secs, .. } => Some(
+                now.checked_sub(Duration::seconds(
+                    (*max_age_secs).min(i64::MAX as u64) as i64
+                ))
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            ),
+        })
+        .collect()
+}
+
 fn _map_to_offsetdatetime(
     maybe_dt_str: &Option<String>,
 ) -> Result<Option<OffsetDateTime>, ProcessingError> {
@@ -125,34 +216,112 @@ fn _map_to_offsetdatetime(
 
 fn _get_trigger_permit(
     pipeline_model: &PipelineRestModel,
-) -> Result<TriggerPermitType, ProcessingError> {
-    let maybe_permit = match &pipeline_model.callback_token {
-        Some(token) => Some(PipelineTriggerPermit {
-            content: String::from(token),
-            is_expired: false,
-        }),
+    now: &OffsetDateTime,
+) -> Result<Option<PipelineTriggerPermit>, ProcessingError> {
+    let token = match &pipeline_model.callback_token {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+    let issued_at = _map_to_offsetdatetime(&pipeline_model.callback_token_issued_at)?;
+    // NOTE: permits without an issue time or a ttl never expire on their
+    // own, only when the API already marks them as expired. The same goes
+    // for a ttl so large that the expiry time is out of range.
+    let expires_at = match (issued_at, pipeline_model.callback_token_ttl_secs) {
+        (Some(issued_at), Some(ttl_secs)) => {
+            issued_at.checked_add(Duration::seconds(ttl_secs.min(i64::MAX as u64) as i64))
+        }
         _ => None,
     };
-    match pipeline_model.trigger_rule.as_str() {
-        "LENIENT" => Ok(TriggerPermitType::Lenient(maybe_permit)),
-        "STRICT" => Ok(TriggerPermitType::Strict(maybe_permit)),
-        _ => Err(ProcessingError::UnrecognizedTriggerType(String::from(
-            "Error: Unrecognized trigger permit type.",
+    let is_expiry_recorded = pipeline_model.callback_token_expired.unwrap_or(false);
+    Ok(Some(PipelineTriggerPermit {
+        content: String::from(token),
+        expires_at,
+        is_expired: is_expiry_recorded || expires_at.is_some_and(|expires_at| expires_at <= *now),
+        is_expiry_recorded,
+    }))
+}
+
+fn _get_trigger_rule(
+    pipeline_model: &PipelineRestModel,
+    trigger_rules: &TriggerRuleRegistry,
+) -> Result<Box<dyn TriggerRule>, ProcessingError> {
+    trigger_rules.build(
+        &pipeline_model.trigger_rule,
+        &TriggerRuleParameters {
+            pipeline_id: &pipeline_model.id,
+            dependencies: &_dependency_url_strings(pipeline_model),
+            quorum_threshold: pipeline_model.quorum_threshold,
+            dependency_expression: pipeline_model.dependency_expression.as_deref(),
+            rule_parameters: pipeline_model.rule_parameters.as_ref(),
+        },
+    )
+}
+
+pub fn get_failure_policy(
+    pipeline_model: &PipelineRestModel,
+    default_policy: &FailurePolicy,
+) -> Result<FailurePolicy, ProcessingError> {
+    match pipeline_model.failure_policy.as_deref() {
+        Some("RETRY") => Ok(FailurePolicy::Retry),
+        Some("NOTIFY") => Ok(FailurePolicy::Notify),
+        Some("BLOCK") => Ok(FailurePolicy::Block),
+        None => Ok(default_policy.clone()),
+        Some(other) => Err(ProcessingError::UnrecognizedFailurePolicy(format!(
+            "Error: Unrecognized failure policy {} for pipeline {}.",
+            other, &pipeline_model.id
         ))),
     }
 }
 
-#[derive(Deserialize, Clone)]
+pub fn get_max_retries(pipeline_model: &PipelineRestModel, default_max_retries: u32) -> u32 {
+    pipeline_model.max_retries.unwrap_or(default_max_retries)
+}
+
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct PipelineRestModel {
     id: String,
     description: String,
     last_success_time: Option<String>,
-    source_dependencies: Vec<String>,
+    source_dependencies: Vec<SourceDependency>,
     trigger_rule: String,
     callback_token: Option<String>,
+    #[serde(default)]
+    callback_token_issued_at: Option<String>,
+    #[serde(default)]
+    callback_token_ttl_secs: Option<u64>,
+    #[serde(default)]
+    callback_token_expired: Option<bool>,
+    #[serde(default)]
+    failure_policy: Option<String>,
+    #[serde(default)]
+    max_retries: Option<u32>,
+    #[serde(default)]
+    quorum_threshold: Option<usize>,
+    #[serde(default)]
+    dependency_expression: Option<String>,
+    #[serde(default)]
+    rule_parameters: Option<serde_json::Value>,
+}
+
+// NOTE: a plain url is fresh with any event since the last success; a
+// windowed dependency also needs that event to be at most max_age_secs old.
+#[derive(Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum SourceDependency {
+    Url(String),
+    Windowed { url: String, max_age_secs: u64 },
+}
+
+impl SourceDependency {
+    pub fn url(&self) -> &str {
+        match self {
+            SourceDependency::Url(url) => url,
+            SourceDependency::Windowed { url, .. } => url,
+        }
+    }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct DataSourceRestModel {
     id: String,
     description: String,
@@ -161,15 +330,32 @@ pub struct DataSourceRestModel {
 
 pub async fn get_data_pipelines(
     event_payload: EventPayload,
-    endpoint_prefix: &str,
-    aws_region: &str,
-    client: &Client,
-) -> Result<Vec<DataPipeline>, ProcessingError> {
-    let fn_fetch_data_source = |ds_id: String| async move {
-        fetch_data_source_model(&ds_id, endpoint_prefix, aws_region, client).await
-    };
+    backend: &Backend,
+    clients: &Clients,
+    trigger_rules: &TriggerRuleRegistry,
+    concurrency_limit: usize,
+) -> Result<Vec<Result<DataPipeline, PipelineError>>, ProcessingError> {
+    let fn_fetch_data_source =
+        |ds_id: String| async move { fetch_data_source_model(&ds_id, backend, clients).await };
     let fn_fetch_data_pipeline = |pipeline_id: String| async move {
-        fetch_pipeline_model(&pipeline_id, endpoint_prefix, aws_region, client).await
+        fetch_pipeline_model(&pipeline_id, backend, clients).await
     };
-    gather_data_pipelines(event_payload, fn_fetch_data_source, fn_fetch_data_pipeline).await
+    gather_data_pipelines(
+        event_payload,
+        fn_fetch_data_source,
+        fn_fetch_data_pipeline,
+        trigger_rules,
+        concurrency_limit,
+    )
+    .await
+}
+
+pub async fn get_waiting_data_pipelines(
+    backend: &Backend,
+    clients: &Clients,
+    trigger_rules: &TriggerRuleRegistry,
+) -> Result<Vec<Result<DataPipeline, PipelineError>>, ProcessingError> {
+    let fn_fetch_all_pipelines =
+        || async move { fetch_all_pipeline_models(backend, clients).await };
+    gather_waiting_data_pipelines(fn_fetch_all_pipelines, trigger_rules).await
 }
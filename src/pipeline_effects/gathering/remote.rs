@@ -2,23 +2,23 @@ use super::{
     basenames, construct_endpoint_url, fetch_rest_model, DataSourceRestModel, PipelineRestModel,
     ProcessingError,
 };
-use reqwest::Client;
+use crate::config::Clients;
+use crate::routing::Backend;
 use std::collections::HashMap;
 
 pub async fn fetch_data_source_model(
     data_source_id: &str,
-    endpoint_prefix: &str,
-    aws_region: &str,
-    client: &Client,
+    backend: &Backend,
+    clients: &Clients,
 ) -> Result<DataSourceRestModel, ProcessingError> {
     let endpoint_url = construct_endpoint_url(
-        endpoint_prefix,
+        &backend.endpoint_prefix,
         &vec![String::from("data-sources"), String::from(data_source_id)],
     )?;
     fetch_rest_model::<DataSourceRestModel, String>(
         endpoint_url.as_str(),
-        aws_region,
-        client,
+        backend,
+        clients,
         "".to_string(),
         &HashMap::new(),
         "GET",
@@ -32,18 +32,34 @@ pub async fn fetch_data_source_model(
 
 pub async fn fetch_pipeline_model(
     pipeline_id: &str,
-    endpoint_prefix: &str,
-    aws_region: &str,
-    client: &Client,
+    backend: &Backend,
+    clients: &Clients,
 ) -> Result<PipelineRestModel, ProcessingError> {
     let endpoint_url = construct_endpoint_url(
-        endpoint_prefix,
+        &backend.endpoint_prefix,
         &vec![String::from("pipelines"), String::from(pipeline_id)],
     )?;
     fetch_rest_model::<PipelineRestModel, String>(
         endpoint_url.as_str(),
-        aws_region,
-        client,
+        backend,
+        clients,
+        "".to_string(),
+        &HashMap::new(),
+        "GET",
+    )
+    .await
+}
+
+pub async fn fetch_all_pipeline_models(
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<Vec<PipelineRestModel>, ProcessingError> {
+    let endpoint_url =
+        construct_endpoint_url(&backend.endpoint_prefix, &vec![String::from("pipelines")])?;
+    fetch_rest_model::<Vec<PipelineRestModel>, String>(
+        endpoint_url.as_str(),
+        backend,
+        clients,
         "".to_string(),
         &HashMap::new(),
         "GET",
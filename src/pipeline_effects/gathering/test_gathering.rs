@@ -1,7 +1,6 @@
 use super::*;
 use crate::entities::EventPayload;
-use crate::pipeline_effects::gathering::TriggerPermitType;
-use crate::pipeline_effects::ProcessingError;
+use crate::pipeline_effects::{ProcessingError, RemoteFailure};
 use rand::thread_rng;
 use rand::{self, Rng};
 use time::format_description::well_known::iso8601::Iso8601;
@@ -42,24 +41,34 @@ async fn gather_data_pipelines_test_happy_path_data_source_event_one_dependent_p
             id: String::from(&id),
             description: format!("pipeline: {}", &id),
             last_success_time: Some(dt.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: None,
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_ok());
     let data_pipelines = result.unwrap();
     assert_eq!(data_pipelines.len(), 1);
-    let data_pipeline = &data_pipelines[0];
+    let data_pipeline = data_pipelines[0].as_ref().unwrap();
     assert_eq!(
         data_pipeline.id,
         format!("pipeline{}", rand_num_pipeline_id)
@@ -72,13 +81,8 @@ async fn gather_data_pipelines_test_happy_path_data_source_event_one_dependent_p
         data_pipeline.last_success_time.unwrap(),
         OffsetDateTime::from_unix_timestamp(unix_ts).unwrap()
     );
-    assert!(
-        if let TriggerPermitType::Lenient(None) = data_pipeline.permit {
-            true
-        } else {
-            false
-        }
-    );
+    assert_eq!(data_pipeline.trigger_rule.describe(), "lenient");
+    assert!(data_pipeline.permit.is_none());
     assert_eq!(data_pipeline.dependency_urls.len(), 1);
     assert_eq!(
         data_pipeline.dependency_urls[0],
@@ -128,25 +132,35 @@ async fn gather_data_pipelines_test_happy_path_data_source_event_two_dependent_p
             id: String::from(&id),
             description: format!("pipeline: {}", &id),
             last_success_time: dt_option,
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("STRICT"),
             callback_token: token_option,
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_ok());
     let data_pipelines = result.unwrap();
     assert_eq!(data_pipelines.len(), 2);
     // preserve the order in DataSourceRestModel.dependent_pipelines. may be too restrictive?
-    let first_data_pipeline = &data_pipelines[0];
+    let first_data_pipeline = data_pipelines[0].as_ref().unwrap();
     assert_eq!(
         first_data_pipeline.id,
         format!("pipeline{}", first_rand_num_pipeline_id)
@@ -159,7 +173,8 @@ async fn gather_data_pipelines_test_happy_path_data_source_event_two_dependent_p
         first_data_pipeline.last_success_time.unwrap(),
         OffsetDateTime::from_unix_timestamp(unix_ts).unwrap()
     );
-    if let TriggerPermitType::Strict(Some(permit)) = &first_data_pipeline.permit {
+    assert_eq!(first_data_pipeline.trigger_rule.describe(), "strict");
+    if let Some(permit) = &first_data_pipeline.permit {
         assert_eq!(permit.content, format!("token{}", rand_num_token));
     } else {
         assert!(false);
@@ -170,7 +185,7 @@ async fn gather_data_pipelines_test_happy_path_data_source_event_two_dependent_p
         Url::parse(&(format!("https://api.hotpotato.com/v1/source{}", rand_num_source_id)))
             .unwrap()
     );
-    let second_data_pipeline = &data_pipelines[1];
+    let second_data_pipeline = data_pipelines[1].as_ref().unwrap();
     assert_eq!(
         second_data_pipeline.id,
         format!("pipeline{}", second_rand_num_pipeline_id)
@@ -181,11 +196,8 @@ async fn gather_data_pipelines_test_happy_path_data_source_event_two_dependent_p
     );
     assert_eq!(second_data_pipeline.last_success_time, None);
 
-    if let TriggerPermitType::Strict(x) = &second_data_pipeline.permit {
-        assert!(x.is_none())
-    } else {
-        assert!(false)
-    };
+    assert_eq!(second_data_pipeline.trigger_rule.describe(), "strict");
+    assert!(second_data_pipeline.permit.is_none());
     assert_eq!(second_data_pipeline.dependency_urls.len(), 1);
     assert_eq!(
         second_data_pipeline.dependency_urls[0],
@@ -222,25 +234,36 @@ async fn gather_data_pipelines_test_happy_path_lenient_trigger_some_token() {
             id: String::from(&id),
             description: format!("pipeline: {}", &id),
             last_success_time: Some(dt.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: Some(format!("token{}", rand_num_token)),
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_ok());
     let data_pipelines = result.unwrap();
     assert_eq!(data_pipelines.len(), 1);
-    let data_pipeline = &data_pipelines[0];
-    if let TriggerPermitType::Lenient(Some(permit)) = &data_pipeline.permit {
+    let data_pipeline = data_pipelines[0].as_ref().unwrap();
+    assert_eq!(data_pipeline.trigger_rule.describe(), "lenient");
+    if let Some(permit) = &data_pipeline.permit {
         assert_eq!(permit.content, format!("token{}", rand_num_token));
     } else {
         assert!(false);
@@ -283,26 +306,114 @@ async fn gather_data_pipelines_test_unrecognized_trigger_type() {
             id: String::from(&id),
             description: format!("pipeline: {}", &id),
             last_success_time: Some(dt.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from(format!("badtype{}", rand_num_pipeline_id)),
             callback_token: Some(format!("token{}", rand_num_token)),
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
-    assert!(result.is_err());
-    if let Err(ProcessingError::UnrecognizedTriggerType(s)) = result {
-        assert_eq!(s, "Error: Unrecognized trigger permit type.")
-    } else {
-        assert!(false)
+    let data_pipelines = result.unwrap();
+    assert_eq!(data_pipelines.len(), 1);
+    match &data_pipelines[0] {
+        Err(PipelineError {
+            pipeline_id,
+            error: ProcessingError::UnrecognizedTriggerType(s),
+        }) => {
+            assert_eq!(
+                pipeline_id.as_deref(),
+                Some(format!("pipeline{}", rand_num_pipeline_id).as_str())
+            );
+            assert_eq!(s, "Error: Unrecognized trigger permit type.")
+        }
+        _ => panic!("expected an unrecognized trigger type for the pipeline"),
+    };
+}
+
+#[tokio::test]
+async fn gather_data_pipelines_test_failing_pipeline_keeps_siblings() {
+    let event_payload = EventPayload::DataSource {
+        id: String::from("source1"),
+    };
+    let data_source_read_dummy_fn = |id: String| async move {
+        Ok(DataSourceRestModel {
+            id,
+            description: "Some data source description".to_string(),
+            dependent_pipelines: vec![
+                String::from("healthy1"),
+                String::from("badrule"),
+                String::from("unreachable"),
+                String::from("healthy2"),
+            ],
+        })
+    };
+    let data_pipeline_read_dummy_fn = |id: String| async move {
+        if id == "unreachable" {
+            return Err(ProcessingError::ModelFetchFailure(
+                RemoteFailure::transient(String::from("connection reset")),
+            ));
+        }
+        Ok(PipelineRestModel {
+            id: String::from(&id),
+            description: format!("pipeline: {}", &id),
+            last_success_time: None,
+            source_dependencies: vec![SourceDependency::Url(String::from(
+                "https://api.hotpotato.com/v1/source1",
+            ))],
+            trigger_rule: if id == "badrule" {
+                String::from("BADTYPE")
+            } else {
+                String::from("STRICT")
+            },
+            callback_token: None,
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
+        })
     };
+    let data_pipelines = gather_data_pipelines(
+        event_payload,
+        data_source_read_dummy_fn,
+        data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
+    )
+    .await
+    .unwrap();
+    assert_eq!(data_pipelines.len(), 4);
+    assert_eq!(data_pipelines[0].as_ref().unwrap().id, "healthy1");
+    assert_eq!(data_pipelines[3].as_ref().unwrap().id, "healthy2");
+    let failure = data_pipelines[1].as_ref().err().unwrap();
+    assert_eq!(failure.pipeline_id.as_deref(), Some("badrule"));
+    assert!(matches!(
+        failure.error,
+        ProcessingError::UnrecognizedTriggerType(_)
+    ));
+    let failure = data_pipelines[2].as_ref().err().unwrap();
+    assert_eq!(failure.pipeline_id.as_deref(), Some("unreachable"));
+    assert!(failure.error.is_retryable());
 }
 
 #[tokio::test]
@@ -337,18 +448,28 @@ async fn gather_data_pipelines_test_happy_path_data_pipeline_event_single_upstre
             id: String::from(&id),
             description: format!("pipeline description {}: {}", rand_num_desc, &id),
             last_success_time: Some(dt.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: Some(format!("token{}", rand_num_token)),
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     if let Err(error) = &result {
@@ -357,7 +478,7 @@ async fn gather_data_pipelines_test_happy_path_data_pipeline_event_single_upstre
     assert!(result.is_ok());
     let data_pipelines = result.unwrap();
     assert_eq!(data_pipelines.len(), 1);
-    let data_pipeline = &data_pipelines[0];
+    let data_pipeline = data_pipelines[0].as_ref().unwrap();
     assert_eq!(
         data_pipeline.id,
         format!("pipeline{}", rand_num_pipeline_id)
@@ -373,7 +494,8 @@ async fn gather_data_pipelines_test_happy_path_data_pipeline_event_single_upstre
         data_pipeline.last_success_time.unwrap(),
         OffsetDateTime::from_unix_timestamp(unix_ts).unwrap()
     );
-    if let TriggerPermitType::Lenient(Some(permit)) = &data_pipeline.permit {
+    assert_eq!(data_pipeline.trigger_rule.describe(), "lenient");
+    if let Some(permit) = &data_pipeline.permit {
         assert_eq!(permit.content, format!("token{}", rand_num_token));
     } else {
         assert!(false);
@@ -418,18 +540,28 @@ async fn gather_data_pipelines_test_missing_success_time_in_db() {
             id: String::from(&id),
             description: format!("pipeline description {}: {}", rand_num_desc, &id),
             last_success_time: None,
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: Some(format!("token{}", rand_num_token)),
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_err());
@@ -475,18 +607,28 @@ async fn gather_data_pipelines_test_success_time_conflict_in_db() {
             id: String::from(&id),
             description: format!("pipeline description {}: {}", rand_num_desc, &id),
             last_success_time: Some(dt2.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: Some(format!("token{}", rand_num_token)),
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_err());
@@ -532,18 +674,28 @@ async fn gather_data_pipelines_test_missing_permit_content_in_db() {
             id: String::from(&id),
             description: format!("pipeline description {}: {}", rand_num_desc, &id),
             last_success_time: Some(dt2.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: None,
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_err());
@@ -586,18 +738,28 @@ async fn gather_data_pipelines_test_permit_content_conflict_in_db() {
             id: String::from(&id),
             description: format!("pipeline description {}: {}", rand_num_desc, &id),
             last_success_time: Some(dt2.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
-            source_dependencies: vec![format!(
+            source_dependencies: vec![SourceDependency::Url(format!(
                 "https://api.hotpotato.com/v1/source{}",
                 rand_num_source_id
-            )],
+            ))],
             trigger_rule: String::from("LENIENT"),
             callback_token: Some(format!("tokens{}", rand_num_token)),
+            callback_token_issued_at: None,
+            callback_token_ttl_secs: None,
+            callback_token_expired: None,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
         })
     };
     let result = gather_data_pipelines(
         event_payload,
         data_source_read_dummy_fn,
         data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        4,
     )
     .await;
     assert!(result.is_err());
@@ -610,3 +772,344 @@ async fn gather_data_pipelines_test_permit_content_conflict_in_db() {
         assert!(false)
     };
 }
+
+#[tokio::test]
+async fn gather_waiting_data_pipelines_test_only_pipelines_with_token() {
+    let rand_num_token = rand::random::<u32>();
+    let mut rng = thread_rng();
+    let unix_ts = rng.gen_range(338250323..1663680521);
+    let fn_fetch_all_pipelines = || async move {
+        const ENCODED_DT_FMT: u128 = 6651332276409342489074426579873955840u128;
+        let dt = OffsetDateTime::from_unix_timestamp(unix_ts).unwrap();
+        let build_model =
+            |id: &str, trigger_rule: &str, callback_token: Option<String>| PipelineRestModel {
+                id: String::from(id),
+                description: format!("pipeline: {}", id),
+                last_success_time: Some(dt.format(&Iso8601::<ENCODED_DT_FMT>).unwrap()),
+                source_dependencies: vec![SourceDependency::Url(String::from(
+                    "https://api.hotpotato.com/v1/source",
+                ))],
+                trigger_rule: String::from(trigger_rule),
+                callback_token,
+                callback_token_issued_at: None,
+                callback_token_ttl_secs: None,
+                callback_token_expired: None,
+                failure_policy: None,
+                max_retries: None,
+                quorum_threshold: None,
+                dependency_expression: None,
+                rule_parameters: None,
+            };
+        Ok(vec![
+            build_model(
+                "waiting",
+                "STRICT",
+                Some(format!("token{}", rand_num_token)),
+            ),
+            build_model("running", "STRICT", None),
+            build_model("misconfigured", "badtype", Some(String::from("token"))),
+        ])
+    };
+    let result = gather_waiting_data_pipelines(
+        fn_fetch_all_pipelines,
+        &TriggerRuleRegistry::with_builtins(),
+    )
+    .await;
+    let waiting_pipelines = result.unwrap();
+    assert_eq!(waiting_pipelines.len(), 2);
+    let data_pipeline = waiting_pipelines[0].as_ref().unwrap();
+    assert_eq!(data_pipeline.id, "waiting");
+    assert_eq!(data_pipeline.trigger_rule.describe(), "strict");
+    if let Some(permit) = &data_pipeline.permit {
+        assert_eq!(permit.content, format!("token{}", rand_num_token));
+    } else {
+        panic!("expected a strict permit with a token");
+    };
+    let failure = waiting_pipelines[1].as_ref().err().unwrap();
+    assert_eq!(failure.pipeline_id.as_deref(), Some("misconfigured"));
+    assert!(matches!(
+        failure.error,
+        ProcessingError::UnrecognizedTriggerType(_)
+    ));
+}
+
+#[tokio::test]
+async fn gather_data_pipelines_test_bounded_concurrency_keeps_order() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let pipeline_ids: Vec<String> = (0..12)
+        .map(|index| format!("pipeline{}_{}", index, rand::random::<u32>()))
+        .collect();
+    let event_payload = EventPayload::DataSource {
+        id: String::from("source1"),
+    };
+    let in_flight = AtomicUsize::new(0);
+    let max_in_flight = AtomicUsize::new(0);
+    let data_source_read_dummy_fn = |id: String| {
+        let dependent_pipelines = pipeline_ids.clone();
+        async move {
+            Ok(DataSourceRestModel {
+                id,
+                description: "Some data source description".to_string(),
+                dependent_pipelines,
+            })
+        }
+    };
+    let data_pipeline_read_dummy_fn = |id: String| {
+        let in_flight = &in_flight;
+        let max_in_flight = &max_in_flight;
+        async move {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(current, Ordering::SeqCst);
+            // NOTE: later pipelines yield less, so they finish first.
+            let index: usize = id[8..id.find('_').unwrap()].parse().unwrap();
+            for _ in 0..(12 - index) {
+                tokio::task::yield_now().await;
+            }
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(PipelineRestModel {
+                id: String::from(&id),
+                description: format!("pipeline: {}", &id),
+                last_success_time: None,
+                source_dependencies: vec![],
+                trigger_rule: String::from("STRICT"),
+                callback_token: None,
+                callback_token_issued_at: None,
+                callback_token_ttl_secs: None,
+                callback_token_expired: None,
+                failure_policy: None,
+                max_retries: None,
+                quorum_threshold: None,
+                dependency_expression: None,
+                rule_parameters: None,
+            })
+        }
+    };
+    let data_pipelines = gather_data_pipelines(
+        event_payload,
+        data_source_read_dummy_fn,
+        data_pipeline_read_dummy_fn,
+        &TriggerRuleRegistry::with_builtins(),
+        3,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        data_pipelines
+            .iter()
+            .map(|data_pipeline| String::from(&data_pipeline.as_ref().unwrap().id))
+            .collect::<Vec<String>>(),
+        pipeline_ids
+    );
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn get_trigger_rule_test_quorum_threshold() {
+    let build_model = |quorum_threshold: Option<usize>| PipelineRestModel {
+        id: format!("pipeline{}", rand::random::<u32>()),
+        description: String::from("quorum pipeline"),
+        last_success_time: None,
+        source_dependencies: (0..5)
+            .map(|index| {
+                SourceDependency::Url(format!("https://api.hotpotato.com/v1/source{}", index))
+            })
+            .collect(),
+        trigger_rule: String::from("QUORUM"),
+        callback_token: Some(String::from("token")),
+        callback_token_issued_at: None,
+        callback_token_ttl_secs: None,
+        callback_token_expired: None,
+        failure_policy: None,
+        max_retries: None,
+        quorum_threshold,
+        dependency_expression: None,
+        rule_parameters: None,
+    };
+    let trigger_rules = TriggerRuleRegistry::with_builtins();
+    let trigger_rule = _get_trigger_rule(&build_model(Some(3)), &trigger_rules).unwrap();
+    assert_eq!(trigger_rule.describe(), "quorum(3)");
+    for quorum_threshold in [None, Some(0), Some(6)] {
+        assert!(matches!(
+            _get_trigger_rule(&build_model(quorum_threshold), &trigger_rules),
+            Err(ProcessingError::InvalidQuorumThreshold(_))
+        ));
+    }
+}
+
+#[test]
+fn get_trigger_rule_test_dependency_expression() {
+    let build_model = |dependency_expression: Option<&str>| PipelineRestModel {
+        id: format!("pipeline{}", rand::random::<u32>()),
+        description: String::from("expression pipeline"),
+        last_success_time: None,
+        source_dependencies: vec![
+            SourceDependency::Url(String::from("https://api.hotpotato.com/v1/orders")),
+            SourceDependency::Url(String::from("https://api.hotpotato.com/v1/customers")),
+        ],
+        trigger_rule: String::from("EXPRESSION"),
+        callback_token: None,
+        callback_token_issued_at: None,
+        callback_token_ttl_secs: None,
+        callback_token_expired: None,
+        failure_policy: None,
+        max_retries: None,
+        quorum_threshold: None,
+        dependency_expression: dependency_expression.map(String::from),
+        rule_parameters: None,
+    };
+    let trigger_rules = TriggerRuleRegistry::with_builtins();
+    let trigger_rule =
+        _get_trigger_rule(&build_model(Some("orders AND customers")), &trigger_rules).unwrap();
+    assert_eq!(trigger_rule.describe(), "expression(orders AND customers)");
+    for dependency_expression in [None, Some("orders AND (customers")] {
+        assert!(matches!(
+            _get_trigger_rule(&build_model(dependency_expression), &trigger_rules),
+            Err(ProcessingError::InvalidDependencyExpression(_))
+        ));
+    }
+}
+
+struct _MinFreshRule {
+    min_fresh: usize,
+}
+
+impl TriggerRule for _MinFreshRule {
+    fn describe(&self) -> String {
+        format!("min_fresh({})", self.min_fresh)
+    }
+
+    fn is_satisfied(
+        &self,
+        _last_success_time: &OffsetDateTime,
+        _dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        None
+    }
+}
+
+#[test]
+fn get_trigger_rule_test_custom_rule_reads_rule_parameters() {
+    let pipeline_model: PipelineRestModel = serde_json::from_value(serde_json::json!({
+        "id": "pipeline1",
+        "description": "custom pipeline",
+        "last_success_time": null,
+        "source_dependencies": ["https://api.hotpotato.com/v1/orders"],
+        "trigger_rule": "MIN_FRESH",
+        "callback_token": null,
+        "rule_parameters": {"min_fresh": 2}
+    }))
+    .unwrap();
+    let mut trigger_rules = TriggerRuleRegistry::with_builtins();
+    trigger_rules.register("MIN_FRESH", |parameters| {
+        let min_fresh = parameters
+            .rule_parameters
+            .and_then(|rule_parameters| rule_parameters.get("min_fresh"))
+            .and_then(|min_fresh| min_fresh.as_u64())
+            .ok_or(ProcessingError::UnrecognizedTriggerType(format!(
+                "Pipeline {} has no min_fresh rule parameter.",
+                parameters.pipeline_id
+            )))?;
+        Ok(Box::new(_MinFreshRule {
+            min_fresh: min_fresh as usize,
+        }))
+    });
+    let trigger_rule = _get_trigger_rule(&pipeline_model, &trigger_rules).unwrap();
+    assert_eq!(trigger_rule.describe(), "min_fresh(2)");
+    let pipeline_model = PipelineRestModel {
+        rule_parameters: None,
+        ..pipeline_model
+    };
+    assert!(matches!(
+        _get_trigger_rule(&pipeline_model, &trigger_rules),
+        Err(ProcessingError::UnrecognizedTriggerType(_))
+    ));
+}
+
+#[test]
+fn get_trigger_permit_test_expiry() {
+    let build_model =
+        |issued_at: Option<&str>, ttl_secs: Option<u64>, expired: Option<bool>| PipelineRestModel {
+            id: format!("pipeline{}", rand::random::<u32>()),
+            description: String::from("waiting pipeline"),
+            last_success_time: None,
+            source_dependencies: vec![],
+            trigger_rule: String::from("LENIENT"),
+            callback_token: Some(format!("token{}", rand::random::<u32>())),
+            callback_token_issued_at: issued_at.map(String::from),
+            callback_token_ttl_secs: ttl_secs,
+            callback_token_expired: expired,
+            failure_policy: None,
+            max_retries: None,
+            quorum_threshold: None,
+            dependency_expression: None,
+            rule_parameters: None,
+        };
+    let now = OffsetDateTime::parse("2023-06-01T12:00:00Z", &Iso8601::DEFAULT).unwrap();
+    let permit = |model: &PipelineRestModel| _get_trigger_permit(model, &now).unwrap().unwrap();
+
+    let fresh = permit(&build_model(Some("2023-06-01T11:00:00Z"), Some(7200), None));
+    assert!(!fresh.is_expired);
+    assert_eq!(
+        fresh.expires_at,
+        Some(OffsetDateTime::parse("2023-06-01T13:00:00Z", &Iso8601::DEFAULT).unwrap())
+    );
+
+    let stale = permit(&build_model(Some("2023-06-01T11:00:00Z"), Some(3600), None));
+    assert!(stale.is_expired);
+    assert!(!stale.is_expiry_recorded);
+
+    let without_ttl = permit(&build_model(Some("2023-01-01T00:00:00Z"), None, None));
+    assert!(!without_ttl.is_expired);
+    assert_eq!(without_ttl.expires_at, None);
+
+    let huge_ttl = permit(&build_model(
+        Some("2023-01-01T00:00:00Z"),
+        Some(u64::MAX),
+        None,
+    ));
+    assert!(!huge_ttl.is_expired);
+    assert_eq!(huge_ttl.expires_at, None);
+
+    let recorded = permit(&build_model(None, None, Some(true)));
+    assert!(recorded.is_expired);
+    assert!(recorded.is_expiry_recorded);
+
+    let mut model = build_model(Some("yesterday"), Some(60), None);
+    assert!(matches!(
+        _get_trigger_permit(&model, &now),
+        Err(ProcessingError::DatatimeParseFailure(_))
+    ));
+    model.callback_token = None;
+    assert!(_get_trigger_permit(&model, &now).unwrap().is_none());
+}
+
+#[test]
+fn source_dependencies_test_plain_and_windowed() {
+    let pipeline_model: PipelineRestModel = serde_json::from_value(serde_json::json!({
+        "id": "pipeline",
+        "description": "windowed pipeline",
+        "last_success_time": null,
+        "source_dependencies": [
+            "https://api.hotpotato.com/v1/orders",
+            {"url": "https://api.hotpotato.com/v1/customers", "max_age_secs": 21600}
+        ],
+        "trigger_rule": "STRICT",
+        "callback_token": null
+    }))
+    .unwrap();
+    assert_eq!(
+        _dependency_url_strings(&pipeline_model),
+        vec![
+            String::from("https://api.hotpotato.com/v1/orders"),
+            String::from("https://api.hotpotato.com/v1/customers"),
+        ]
+    );
+    let now = OffsetDateTime::parse("2023-06-01T12:00:00Z", &Iso8601::DEFAULT).unwrap();
+    assert_eq!(
+        _map_to_window_starts(&pipeline_model.source_dependencies, &now),
+        vec![
+            None,
+            Some(OffsetDateTime::parse("2023-06-01T06:00:00Z", &Iso8601::DEFAULT).unwrap())
+        ]
+    );
+}
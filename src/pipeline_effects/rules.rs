@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod test_rules;
+
+use super::expressions::DependencyExpression;
+use super::ProcessingError;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+// NOTE: a rule decides readiness from the event times fetched so far, one
+// entry per dependency in order and None for dependencies not fetched yet.
+// Returning None asks for the next dependency; once a rule returns Some the
+// remaining dependencies are not fetched.
+pub trait TriggerRule: Send + Sync {
+    fn describe(&self) -> String;
+
+    fn is_satisfied(
+        &self,
+        last_success_time: &OffsetDateTime,
+        dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool>;
+}
+
+pub struct TriggerRuleParameters<'a> {
+    pub pipeline_id: &'a str,
+    pub dependencies: &'a [String],
+    pub quorum_threshold: Option<usize>,
+    pub dependency_expression: Option<&'a str>,
+    // NOTE: free-form per-pipeline settings for rules that are not built in;
+    // the builtin rules only read their own fields above.
+    pub rule_parameters: Option<&'a serde_json::Value>,
+}
+
+pub type TriggerRuleFactory = Arc<
+    dyn Fn(&TriggerRuleParameters) -> Result<Box<dyn TriggerRule>, ProcessingError> + Send + Sync,
+>;
+
+#[derive(Clone, Default)]
+pub struct TriggerRuleRegistry {
+    factories: HashMap<String, TriggerRuleFactory>,
+}
+
+impl fmt::Debug for TriggerRuleRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+impl TriggerRuleRegistry {
+    pub fn new() -> TriggerRuleRegistry {
+        TriggerRuleRegistry {
+            factories: HashMap::new(),
+        }
+    }
+
+    pub fn with_builtins() -> TriggerRuleRegistry {
+        let mut registry = TriggerRuleRegistry::new();
+        registry.register("LENIENT", |_| Ok(Box::new(LenientRule)));
+        registry.register("STRICT", |_| Ok(Box::new(StrictRule)));
+        registry.register("QUORUM", |parameters| {
+            Ok(Box::new(QuorumRule::from_parameters(parameters)?))
+        });
+        registry.register("EXPRESSION", |parameters| {
+            Ok(Box::new(ExpressionRule::from_parameters(parameters)?))
+        });
+        registry
+    }
+
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&TriggerRuleParameters) -> Result<Box<dyn TriggerRule>, ProcessingError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.factories.insert(String::from(name), Arc::new(factory));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn build(
+        &self,
+        name: &str,
+        parameters: &TriggerRuleParameters,
+    ) -> Result<Box<dyn TriggerRule>, ProcessingError> {
+        match self.factories.get(name) {
+            Some(factory) => factory(parameters),
+            None => Err(ProcessingError::UnrecognizedTriggerType(String::from(
+                "Error: Unrecognized trigger permit type.",
+            ))),
+        }
+    }
+}
+
+pub fn is_fresh(event_times: &[OffsetDateTime], last_success_time: &OffsetDateTime) -> bool {
+    event_times.iter().any(|t| t.ge(last_success_time))
+}
+
+fn _freshness(
+    last_success_time: &OffsetDateTime,
+    dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+) -> Vec<Option<bool>> {
+    dependency_event_times
+        .iter()
+        .map(|maybe_times| {
+            maybe_times
+                .as_ref()
+                .map(|times| is_fresh(times, last_success_time))
+        })
+        .collect()
+}
+
+pub struct LenientRule;
+
+impl TriggerRule for LenientRule {
+    fn describe(&self) -> String {
+        String::from("lenient")
+    }
+
+    fn is_satisfied(
+        &self,
+        last_success_time: &OffsetDateTime,
+        dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        let freshness = _freshness(last_success_time, dependency_event_times);
+        if freshness.contains(&Some(true)) {
+            // early answer here might save us a few
+            // extra calls for fetching event times
+            Some(true)
+        } else if freshness.contains(&None) {
+            None
+        } else {
+            Some(false)
+        }
+    }
+}
+
+pub struct StrictRule;
+
+impl TriggerRule for StrictRule {
+    fn describe(&self) -> String {
+        String::from("strict")
+    }
+
+    fn is_satisfied(
+        &self,
+        last_success_time: &OffsetDateTime,
+        dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        let freshness = _freshness(last_success_time, dependency_event_times);
+        if freshness.contains(&None) {
+            return None;
+        }
+        // NOTE: pipelines with no data source events
+        // will not be triggered for now. Flip this to
+        // reverse this decision.
+        Some(!freshness.is_empty() && freshness.iter().all(|fresh| *fresh == Some(true)))
+    }
+}
+
+pub struct QuorumRule {
+    threshold: usize,
+}
+
+impl QuorumRule {
+    pub fn from_parameters(parameters: &TriggerRuleParameters) -> Result<Self, ProcessingError> {
+        match parameters.quorum_threshold {
+            Some(threshold) if threshold > 0 && threshold <= parameters.dependencies.len() => {
+                Ok(QuorumRule { threshold })
+            }
+            other => Err(ProcessingError::InvalidQuorumThreshold(format!(
+                "Error: Quorum threshold {:?} of pipeline {} must be between 1 and its {} dependencies.",
+                other,
+                parameters.pipeline_id,
+                parameters.dependencies.len()
+            ))),
+        }
+    }
+}
+
+impl TriggerRule for QuorumRule {
+    fn describe(&self) -> String {
+        format!("quorum({})", self.threshold)
+    }
+
+    fn is_satisfied(
+        &self,
+        last_success_time: &OffsetDateTime,
+        dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        let freshness = _freshness(last_success_time, dependency_event_times);
+        let fresh_count = freshness
+            .iter()
+            .filter(|fresh| **fresh == Some(true))
+            .count();
+        let unknown_count = freshness.iter().filter(|fresh| fresh.is_none()).count();
+        if fresh_count >= self.threshold {
+            Some(true)
+        } else if fresh_count + unknown_count < self.threshold {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct ExpressionRule {
+    expression: DependencyExpression,
+}
+
+impl ExpressionRule {
+    pub fn from_parameters(parameters: &TriggerRuleParameters) -> Result<Self, ProcessingError> {
+        match parameters.dependency_expression {
+            Some(expression) => Ok(ExpressionRule {
+                expression: DependencyExpression::parse(expression, parameters.dependencies)?,
+            }),
+            None => Err(ProcessingError::InvalidDependencyExpression(format!(
+                "Error: Pipeline {} has an EXPRESSION trigger rule but no dependency expression.",
+                parameters.pipeline_id
+            ))),
+        }
+    }
+}
+
+impl TriggerRule for ExpressionRule {
+    fn describe(&self) -> String {
+        format!("expression({})", self.expression.source())
+    }
+
+    fn is_satisfied(
+        &self,
+        last_success_time: &OffsetDateTime,
+        dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        self.expression
+            .evaluate(&_freshness(last_success_time, dependency_event_times))
+    }
+}
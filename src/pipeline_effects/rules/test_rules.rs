@@ -0,0 +1,165 @@
+use super::*;
+use time::Duration;
+
+fn _last_success_time() -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap()
+}
+
+fn _fresh() -> Option<Vec<OffsetDateTime>> {
+    Some(vec![_last_success_time() + Duration::hours(1)])
+}
+
+fn _stale() -> Option<Vec<OffsetDateTime>> {
+    Some(vec![_last_success_time() - Duration::hours(1)])
+}
+
+fn _parameters<'a>(pipeline_id: &'a str, dependencies: &'a [String]) -> TriggerRuleParameters<'a> {
+    TriggerRuleParameters {
+        pipeline_id,
+        dependencies,
+        quorum_threshold: None,
+        dependency_expression: None,
+        rule_parameters: None,
+    }
+}
+
+struct NeverRule;
+
+impl TriggerRule for NeverRule {
+    fn describe(&self) -> String {
+        String::from("never")
+    }
+
+    fn is_satisfied(
+        &self,
+        _last_success_time: &OffsetDateTime,
+        _dependency_event_times: &[Option<Vec<OffsetDateTime>>],
+    ) -> Option<bool> {
+        Some(false)
+    }
+}
+
+#[test]
+fn trigger_rule_registry_test_builtins() {
+    let registry = TriggerRuleRegistry::with_builtins();
+    assert_eq!(
+        registry.names(),
+        vec!["EXPRESSION", "LENIENT", "QUORUM", "STRICT"]
+    );
+    let pipeline_id = format!("pipeline{}", rand::random::<u32>());
+    let rule = registry
+        .build("STRICT", &_parameters(&pipeline_id, &[]))
+        .unwrap();
+    assert_eq!(rule.describe(), "strict");
+}
+
+#[test]
+fn trigger_rule_registry_test_unrecognized_rule() {
+    let registry = TriggerRuleRegistry::with_builtins();
+    match registry.build("badtype", &_parameters("pipeline1", &[])) {
+        Err(ProcessingError::UnrecognizedTriggerType(s)) => {
+            assert_eq!(s, "Error: Unrecognized trigger permit type.")
+        }
+        _ => panic!("expected an unrecognized trigger type"),
+    }
+}
+
+#[test]
+fn trigger_rule_registry_test_custom_rule() {
+    let mut registry = TriggerRuleRegistry::with_builtins();
+    registry.register("NEVER", |_| Ok(Box::new(NeverRule)));
+    let rule = registry
+        .build("NEVER", &_parameters("pipeline1", &[]))
+        .unwrap();
+    assert_eq!(rule.describe(), "never");
+    assert_eq!(
+        rule.is_satisfied(&_last_success_time(), &[_fresh(), None]),
+        Some(false)
+    );
+}
+
+#[test]
+fn lenient_rule_test_is_satisfied() {
+    let last_success_time = _last_success_time();
+    assert_eq!(
+        LenientRule.is_satisfied(&last_success_time, &[_stale(), _fresh(), None]),
+        Some(true)
+    );
+    assert_eq!(
+        LenientRule.is_satisfied(&last_success_time, &[_stale(), None]),
+        None
+    );
+    assert_eq!(
+        LenientRule.is_satisfied(&last_success_time, &[_stale(), _stale()]),
+        Some(false)
+    );
+    assert_eq!(
+        LenientRule.is_satisfied(&last_success_time, &[]),
+        Some(false)
+    );
+}
+
+#[test]
+fn strict_rule_test_is_satisfied() {
+    let last_success_time = _last_success_time();
+    assert_eq!(
+        StrictRule.is_satisfied(&last_success_time, &[_stale(), None]),
+        None
+    );
+    assert_eq!(
+        StrictRule.is_satisfied(&last_success_time, &[_fresh(), _stale()]),
+        Some(false)
+    );
+    assert_eq!(
+        StrictRule.is_satisfied(&last_success_time, &[_fresh(), _fresh()]),
+        Some(true)
+    );
+    assert_eq!(
+        StrictRule.is_satisfied(&last_success_time, &[]),
+        Some(false)
+    );
+}
+
+#[test]
+fn quorum_rule_test_is_satisfied() {
+    let dependencies: Vec<String> = (0..4).map(|index| format!("source{}", index)).collect();
+    let rule = QuorumRule::from_parameters(&TriggerRuleParameters {
+        quorum_threshold: Some(2),
+        .._parameters("pipeline1", &dependencies)
+    })
+    .unwrap();
+    let last_success_time = _last_success_time();
+    assert_eq!(rule.describe(), "quorum(2)");
+    assert_eq!(
+        rule.is_satisfied(&last_success_time, &[_fresh(), _stale(), None, None]),
+        None
+    );
+    assert_eq!(
+        rule.is_satisfied(&last_success_time, &[_fresh(), _stale(), _fresh(), None]),
+        Some(true)
+    );
+    assert_eq!(
+        rule.is_satisfied(&last_success_time, &[_stale(), _stale(), _stale(), None]),
+        Some(false)
+    );
+}
+
+#[test]
+fn expression_rule_test_is_satisfied() {
+    let dependencies = vec![String::from("orders"), String::from("customers")];
+    let rule = ExpressionRule::from_parameters(&TriggerRuleParameters {
+        dependency_expression: Some("orders AND customers"),
+        .._parameters("pipeline1", &dependencies)
+    })
+    .unwrap();
+    let last_success_time = _last_success_time();
+    assert_eq!(rule.describe(), "expression(orders AND customers)");
+    assert_eq!(
+        rule.is_satisfied(&last_success_time, &[_stale(), None]),
+        Some(false)
+    );
+    assert_eq!(
+        rule.is_satisfied(&last_success_time, &[_fresh(), None]),
+        None
+    );
+}
@@ -0,0 +1,67 @@
+use super::*;
+use crate::config::test_config::build_sdk_config;
+
+fn _build_data_pipeline() -> DataPipeline {
+    DataPipeline {
+        id: format!("pipeline{}", rand::random::<u32>()),
+        description: String::from("test pipeline"),
+        last_success_time: None,
+        permit: None,
+        trigger_rule: Box::new(rules::StrictRule),
+        dependency_urls: vec![],
+        dependency_window_starts: vec![],
+    }
+}
+
+#[test]
+fn evaluation_deadline_test_no_deadline_outside_lambda() {
+    assert_eq!(
+        evaluation_deadline(&Context::default(), Duration::from_secs(3)),
+        None
+    );
+}
+
+#[test]
+fn evaluation_deadline_test_subtracts_margin() {
+    let mut context = Context::default();
+    context.deadline = 10_000;
+    assert_eq!(
+        evaluation_deadline(&context, Duration::from_secs(3)),
+        Some(UNIX_EPOCH + Duration::from_secs(7))
+    );
+    assert!(evaluation_deadline(&context, Duration::from_secs(30)).unwrap() < UNIX_EPOCH);
+}
+
+#[tokio::test]
+async fn evaluate_pipelines_test_defers_pipelines_past_deadline() {
+    let data_pipelines = [_build_data_pipeline(), _build_data_pipeline()];
+    let backend = Backend {
+        endpoint_prefix: String::from("http://localhost:1/"),
+        aws_region: String::from("eu-west-1"),
+        credentials_profile: None,
+        role_arn: None,
+    };
+    let env_vars = std::collections::HashMap::from([
+        (
+            String::from("ENV_ENDPOINT_URL"),
+            String::from(&backend.endpoint_prefix),
+        ),
+        (String::from("ENV_AWS_REGION"), String::from("eu-west-1")),
+    ]);
+    let config = Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap();
+    let sfn_client = aws_sdk_sfn::Client::from_conf(aws_sdk_sfn::Config::builder().build());
+    let context = EffectContext {
+        config: &config,
+        backend: &backend,
+        clients: &config.clients,
+        sfn_client: &sfn_client,
+        recorder: None,
+    };
+    let decisions = _evaluate_pipelines(data_pipelines.iter(), context, Some(UNIX_EPOCH)).await;
+    assert_eq!(decisions.len(), 2);
+    for (decision, data_pipeline) in decisions.iter().zip(data_pipelines.iter()) {
+        assert_eq!(decision.pipeline_id, data_pipeline.id);
+        assert_eq!(decision.verdict, Verdict::Deferred);
+        assert_eq!(decision.error, None);
+    }
+}
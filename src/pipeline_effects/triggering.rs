@@ -1,25 +1,33 @@
 mod remote;
+#[cfg(test)]
+mod test_triggering;
 
 use super::{
-    endpoint::fetch_rest_model, DataPipeline, PipelineTriggerPermit, ProcessingError,
-    TriggerPermitType,
+    decisions::{format_time, PipelineDecision, Verdict},
+    endpoint::{construct_endpoint_url, fetch_rest_model},
+    rules::is_fresh,
+    DataPipeline, PipelineTriggerPermit, ProcessingError,
 };
+use crate::config::Clients;
+use crate::dry_run::{EffectRecorder, PlannedEffect};
+use crate::routing::Backend;
 use aws_sdk_sfn;
 use remote::{
-    fetch_latest_datasource_events, fetch_state_machine_names, is_task_ready, send_task_heartbeat,
-    send_task_success,
+    fetch_latest_datasource_events, is_task_ready, mark_permit_as_expired, send_task_failure,
+    send_task_heartbeat, send_task_success,
 };
-use reqwest::Client;
 use std::future::Future;
 use time::OffsetDateTime;
 use tracing::{event, Level};
 use url::Url;
 
+// NOTE: state machines that want a fresh permit retry their task token
+// state on this error, which issues a new callback token.
+const PERMIT_EXPIRED_ERROR: &str = "PermitExpired";
+
 fn get_task_token(data_pipeline: &DataPipeline) -> Result<String, ProcessingError> {
     match &data_pipeline.permit {
-        TriggerPermitType::Lenient(Some(permit)) | TriggerPermitType::Strict(Some(permit)) => {
-            Ok((&permit.content).to_string())
-        }
+        Some(permit) => Ok((&permit.content).to_string()),
         _ => Err(ProcessingError::MissingPipelinePermit(format!(
             "Missing pipeline trigger permit for {}",
             &data_pipeline.id
@@ -41,32 +49,118 @@ where
         .any(|sm_name| -> bool { sm_name.eq(&data_pipeline.id) }))
 }
 
-async fn trigger_pipeline(data_pipeline: &DataPipeline) -> Result<(), ProcessingError> {
+async fn trigger_pipeline(
+    data_pipeline: &DataPipeline,
+    sfn_client: &aws_sdk_sfn::Client,
+) -> Result<(), ProcessingError> {
     let task_token = get_task_token(data_pipeline)?;
-    let config = aws_config::load_from_env().await;
-    let client = aws_sdk_sfn::Client::new(&config);
-    send_task_heartbeat(&task_token, &client).await?;
-    send_task_success(&task_token, &client).await
+    send_task_heartbeat(&task_token, sfn_client).await?;
+    send_task_success(&task_token, sfn_client).await
 }
 
-async fn is_pipeline_ready(data_pipeline: &DataPipeline) -> Result<bool, ProcessingError> {
+fn record_trigger_pipeline(
+    data_pipeline: &DataPipeline,
+    recorder: &EffectRecorder,
+) -> Result<(), ProcessingError> {
+    get_task_token(data_pipeline)?;
+    recorder.record(&data_pipeline.id, PlannedEffect::SendTaskHeartbeat);
+    recorder.record(&data_pipeline.id, PlannedEffect::SendTaskSuccess);
+    Ok(())
+}
+
+// NOTE: readiness is probed with a heartbeat, which extends the task
+// timeout of a waiting pipeline; dry runs record it instead of sending it.
+async fn is_pipeline_ready(
+    data_pipeline: &DataPipeline,
+    sfn_client: &aws_sdk_sfn::Client,
+) -> Result<bool, ProcessingError> {
     let task_token = get_task_token(data_pipeline)?;
-    let config = aws_config::load_from_env().await;
-    let client = aws_sdk_sfn::Client::new(&config);
-    is_task_ready(&task_token, &client).await
+    is_task_ready(&task_token, sfn_client).await
+}
+
+fn _expired_permit(data_pipeline: &DataPipeline) -> Option<&PipelineTriggerPermit> {
+    data_pipeline
+        .permit
+        .as_ref()
+        .filter(|permit| permit.is_expired)
+}
+
+async fn handle_expired_permit<F, FutF, G, FutG>(
+    data_pipeline: &DataPipeline,
+    refresh_expired_permits: bool,
+    fn_mark_permit_as_expired: F,
+    fn_request_fresh_permit: G,
+) -> Result<(), ProcessingError>
+where
+    F: Fn(String, String) -> FutF,
+    FutF: Future<Output = Result<bool, ProcessingError>>,
+    G: Fn(String, String) -> FutG,
+    FutG: Future<Output = Result<bool, ProcessingError>>,
+{
+    let permit = match _expired_permit(data_pipeline) {
+        Some(permit) if !permit.is_expiry_recorded => permit,
+        _ => return Ok(()),
+    };
+    // NOTE: the fresh permit is requested before the expiry is recorded,
+    // since a recorded expiry is never handled again. If the request fails,
+    // the next evaluation retries both steps. The expiry is only recorded
+    // for the expired token, so it cannot mark a fresh permit that the
+    // state machine issued in between.
+    if refresh_expired_permits {
+        let cause = format!(
+            "Trigger permit of pipeline {} expired at {}.",
+            &data_pipeline.id,
+            permit
+                .expires_at
+                .map(format_time)
+                .unwrap_or(String::from("an unknown time"))
+        );
+        let is_requested = fn_request_fresh_permit(String::from(&permit.content), cause).await?;
+        if !is_requested {
+            let mssg = format!(
+                "Could not request a fresh trigger permit for pipeline with id {}; its task is no longer waiting.",
+                &data_pipeline.id
+            );
+            event!(Level::WARN, mssg);
+        }
+    }
+    let is_recorded = fn_mark_permit_as_expired(
+        String::from(&data_pipeline.id),
+        String::from(&permit.content),
+    )
+    .await?;
+    if !is_recorded {
+        let mssg = format!(
+            "Trigger permit of pipeline with id {} was replaced before its expiry was recorded.",
+            &data_pipeline.id
+        );
+        event!(Level::INFO, mssg);
+    }
+    Ok(())
 }
 
 async fn can_trigger_pipeline<'a, F, FutF, G, FutG>(
     data_pipeline: &'a DataPipeline,
     latest_data_source_event_times_fn: F,
     is_pipeline_ready_fn: G,
-) -> Result<bool, ProcessingError>
+) -> Result<PipelineDecision, ProcessingError>
 where
     F: Fn(Url) -> FutF,
     FutF: Future<Output = Result<Vec<OffsetDateTime>, ProcessingError>>,
     G: Fn(&'a DataPipeline) -> FutG,
     FutG: Future<Output = Result<bool, ProcessingError>>,
 {
+    let mut decision = PipelineDecision::new(data_pipeline);
+    if let Some(permit) = _expired_permit(data_pipeline) {
+        decision.reason = match permit.expires_at {
+            Some(expires_at) => format!(
+                "Pipeline trigger permit expired at {}; readiness was not probed.",
+                format_time(expires_at)
+            ),
+            None => String::from("Pipeline trigger permit is expired; readiness was not probed."),
+        };
+        return Ok(decision);
+    }
     let last_pipeline_success_time = match &data_pipeline.last_success_time {
         Some(success_time) => Ok(success_time),
         None => Err(ProcessingError::MissingSuccessTime(format!(
@@ -74,54 +168,128 @@ where
             &data_pipeline.id
         ))),
     }?;
-    let has_new_source_event = match data_pipeline.permit {
-        TriggerPermitType::Lenient(_) => {
-            let mut flag = false;
-            for ds_url in &data_pipeline.dependency_urls {
-                let event_times = latest_data_source_event_times_fn(ds_url.clone()).await?;
-                flag = event_times.iter().any(|t| t.ge(last_pipeline_success_time));
-                if flag {
-                    // early break here might save us a few
-                    // extra calls for fetching event times
-                    break;
-                }
-            }
-            Ok(flag)
+    let dependency_count = data_pipeline.dependency_urls.len();
+    let mut dependency_event_times: Vec<Option<Vec<OffsetDateTime>>> = vec![None; dependency_count];
+    let mut is_satisfied = data_pipeline
+        .trigger_rule
+        .is_satisfied(last_pipeline_success_time, &dependency_event_times);
+    for (index, ds_url) in data_pipeline.dependency_urls.iter().enumerate() {
+        if is_satisfied.is_some() {
+            break;
         }
-        TriggerPermitType::Strict(_) => {
-            let mut flags = vec![];
-            for ds_url in &data_pipeline.dependency_urls {
-                let flag = &latest_data_source_event_times_fn(ds_url.clone())
-                    .await?
-                    .iter()
-                    .any(|t| t.ge(last_pipeline_success_time));
-                flags.push(*flag);
-            }
-            if flags.len() > 0 {
-                Ok(flags.iter().all(|flag| *flag == true))
-            } else {
-                // NOTE: pipelines with no data source events
-                // will not be triggered for now. Flip this to
-                // reverse this decision.
-                Ok(false)
-            }
-        }
-    }?;
-    Ok(has_new_source_event && is_pipeline_ready_fn(data_pipeline).await?)
+        let event_times = latest_data_source_event_times_fn(ds_url.clone()).await?;
+        decision.dependencies[index].newest_event_time =
+            event_times.iter().max().map(|t| format_time(*t));
+        let event_times: Vec<OffsetDateTime> = match data_pipeline
+            .dependency_window_starts
+            .get(index)
+            .copied()
+            .flatten()
+        {
+            Some(window_start) => event_times
+                .into_iter()
+                .filter(|event_time| *event_time >= window_start)
+                .collect(),
+            None => event_times,
+        };
+        decision.dependencies[index].is_fresh =
+            Some(is_fresh(&event_times, last_pipeline_success_time));
+        dependency_event_times[index] = Some(event_times);
+        is_satisfied = data_pipeline
+            .trigger_rule
+            .is_satisfied(last_pipeline_success_time, &dependency_event_times);
+    }
+    let fresh_count = decision
+        .dependencies
+        .iter()
+        .filter(|dependency| dependency.is_fresh == Some(true))
+        .count();
+    let has_new_source_event = is_satisfied.unwrap_or(false);
+    if !has_new_source_event {
+        decision.reason = if dependency_count == 0 {
+            String::from("Pipeline has no dependencies.")
+        } else {
+            format!(
+                "{} of {} dependencies have events since the last success.",
+                fresh_count, dependency_count
+            )
+        };
+        return Ok(decision);
+    }
+    let is_ready = is_pipeline_ready_fn(data_pipeline).await?;
+    decision.is_ready = Some(is_ready);
+    if is_ready {
+        decision.verdict = Verdict::Triggered;
+        decision.reason = format!(
+            "{} of {} dependencies have events since the last success and the pipeline is waiting for a trigger.",
+            fresh_count, dependency_count
+        );
+    } else {
+        decision.reason = String::from("Pipeline is not waiting for a trigger.");
+    }
+    Ok(decision)
 }
 
 pub async fn maybe_trigger_pipeline(
     data_pipeline: &DataPipeline,
-    aws_region: &str,
-    client: &Client,
-) -> Result<(), ProcessingError> {
+    backend: &Backend,
+    clients: &Clients,
+    sfn_client: &aws_sdk_sfn::Client,
+    recorder: Option<&EffectRecorder>,
+    refresh_expired_permits: bool,
+) -> Result<PipelineDecision, ProcessingError> {
     let times_fetching_fn =
-        |url: Url| async move { fetch_latest_datasource_events(&url, aws_region, client).await };
-    if can_trigger_pipeline(data_pipeline, times_fetching_fn, is_pipeline_ready).await? {
-        trigger_pipeline(data_pipeline).await?;
-    } else {
-        let mssg = format!("Pipeline with id {} was not triggered.", &data_pipeline.id);
-        event!(Level::INFO, mssg);
+        |url: Url| async move { fetch_latest_datasource_events(&url, backend, clients).await };
+    let is_pipeline_ready_fn = |data_pipeline| async move {
+        match recorder {
+            // NOTE: without the probe a dry run cannot tell whether the task
+            // is still waiting, so it plans as if it were.
+            Some(recorder) => {
+                get_task_token(data_pipeline)?;
+                recorder.record(&data_pipeline.id, PlannedEffect::SendTaskHeartbeat);
+                Ok(true)
+            }
+            None => is_pipeline_ready(data_pipeline, sfn_client).await,
+        }
     };
-    Ok(())
+    let decision =
+        can_trigger_pipeline(data_pipeline, times_fetching_fn, is_pipeline_ready_fn).await?;
+    if decision.verdict == Verdict::Triggered {
+        match recorder {
+            Some(recorder) => record_trigger_pipeline(data_pipeline, recorder)?,
+            None => trigger_pipeline(data_pipeline, sfn_client).await?,
+        }
+    }
+    let fn_mark_permit_as_expired = |pipeline_id: String, token: String| async move {
+        match recorder {
+            Some(recorder) => {
+                recorder.record(&pipeline_id, PlannedEffect::MarkPermitAsExpired);
+                Ok(true)
+            }
+            None => mark_permit_as_expired(&pipeline_id, &token, backend, clients).await,
+        }
+    };
+    let fn_request_fresh_permit = |token: String, cause: String| async move {
+        match recorder {
+            Some(recorder) => {
+                recorder.record(
+                    &data_pipeline.id,
+                    PlannedEffect::SendTaskFailure {
+                        error: String::from(PERMIT_EXPIRED_ERROR),
+                        cause,
+                    },
+                );
+                Ok(true)
+            }
+            None => send_task_failure(&token, PERMIT_EXPIRED_ERROR, &cause, sfn_client).await,
+        }
+    };
+    handle_expired_permit(
+        data_pipeline,
+        refresh_expired_permits,
+        fn_mark_permit_as_expired,
+        fn_request_fresh_permit,
+    )
+    .await?;
+    Ok(decision)
 }
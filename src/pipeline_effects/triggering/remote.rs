@@ -1,8 +1,16 @@
-use super::{fetch_rest_model, ProcessingError};
-use aws_sdk_sfn::{self, operation::send_task_heartbeat::SendTaskHeartbeatError};
+use super::{construct_endpoint_url, fetch_rest_model, ProcessingError};
+use crate::config::Clients;
+use crate::events::remote::{EventRestModel, EventType};
+use crate::pipeline_effects::endpoint::write_conditional_rest_model;
+use crate::pipeline_effects::{is_transient_sdk_error, RemoteFailure};
+use crate::routing::Backend;
+use aws_sdk_sfn::{
+    self,
+    operation::{
+        send_task_failure::SendTaskFailureError, send_task_heartbeat::SendTaskHeartbeatError,
+    },
+};
 use aws_smithy_http::result::SdkError;
-use reqwest::Client;
-use serde::Deserialize;
 use std::collections::HashMap;
 use time::format_description::well_known::iso8601::Iso8601;
 use time::OffsetDateTime;
@@ -17,10 +25,14 @@ pub async fn send_task_success(
         .send();
     match result_future.await {
         Ok(_) => Ok(()),
-        Err(error) => Err(ProcessingError::RelayTaskSuccessError(format!(
-            "{:?}",
-            error
-        ))),
+        Err(error) if is_transient_sdk_error(&error) => {
+            Err(ProcessingError::RelayTaskSuccessError(
+                RemoteFailure::transient(format!("{:?}", error)),
+            ))
+        }
+        Err(error) => Err(ProcessingError::RelayTaskSuccessError(
+            RemoteFailure::terminal(format!("{:?}", error)),
+        )),
     }
 }
 
@@ -43,10 +55,14 @@ pub async fn fetch_state_machine_names(
             }
             None => Ok(vec![]),
         },
-        Err(error) => Err(ProcessingError::StateMachineFetchingError(format!(
-            "{:?}",
-            error
-        ))),
+        Err(error) if is_transient_sdk_error(&error) => {
+            Err(ProcessingError::StateMachineFetchingError(
+                RemoteFailure::transient(format!("{:?}", error)),
+            ))
+        }
+        Err(error) => Err(ProcessingError::StateMachineFetchingError(
+            RemoteFailure::terminal(format!("{:?}", error)),
+        )),
     }
 }
 
@@ -60,10 +76,17 @@ pub async fn send_task_heartbeat(
         .send();
     match result_future.await {
         Ok(_) => Ok(()),
-        Err(error) => Err(ProcessingError::RelayTaskHeartbeatError(format!(
-            "State machine heatbeat relay failed. Perhaps state machine down or busy.\n{:?}",
-            error
-        ))),
+        Err(error) if is_transient_sdk_error(&error) => {
+            Err(ProcessingError::RelayTaskHeartbeatError(
+                RemoteFailure::transient(format!("{:?}", error)),
+            ))
+        }
+        Err(error) => Err(ProcessingError::RelayTaskHeartbeatError(
+            RemoteFailure::terminal(format!(
+                "State machine heatbeat relay failed. Perhaps state machine down or busy.\n{:?}",
+                error
+            )),
+        )),
     }
 }
 
@@ -77,41 +100,108 @@ pub async fn is_task_ready(
         .send();
     match result_future.await {
         Ok(_) => Ok(true),
+        Err(error) if is_transient_sdk_error(&error) => {
+            Err(ProcessingError::RelayTaskHeartbeatError(
+                RemoteFailure::transient(format!("{:?}", error)),
+            ))
+        }
         Err(SdkError::ServiceError(service_error)) => match service_error.err() {
             SendTaskHeartbeatError::TaskTimedOut(_) => Ok(false),
-            error => Err(ProcessingError::RelayTaskHeartbeatError(format!(
+            error => Err(ProcessingError::RelayTaskHeartbeatError(
+                RemoteFailure::terminal(format!(
+                    "State machine heartbeat relay failed.\n{:?}",
+                    error
+                )),
+            )),
+        },
+        Err(error) => Err(ProcessingError::RelayTaskHeartbeatError(
+            RemoteFailure::terminal(format!(
                 "State machine heartbeat relay failed.\n{:?}",
                 error
-            ))),
+            )),
+        )),
+    }
+}
+
+pub async fn send_task_failure(
+    token: &str,
+    error_name: &str,
+    cause: &str,
+    client: &aws_sdk_sfn::Client,
+) -> Result<bool, ProcessingError> {
+    let result_future = client
+        .send_task_failure()
+        .set_task_token(Some(token.to_string()))
+        .error(error_name)
+        .cause(cause)
+        .send();
+    match result_future.await {
+        Ok(_) => Ok(true),
+        Err(error) if is_transient_sdk_error(&error) => {
+            Err(ProcessingError::RelayTaskFailureError(
+                RemoteFailure::transient(format!("{:?}", error)),
+            ))
+        }
+        Err(SdkError::ServiceError(service_error)) => match service_error.err() {
+            SendTaskFailureError::TaskTimedOut(_) | SendTaskFailureError::TaskDoesNotExist(_) => {
+                Ok(false)
+            }
+            error => Err(ProcessingError::RelayTaskFailureError(
+                RemoteFailure::terminal(format!(
+                    "State machine task failure relay failed.\n{:?}",
+                    error
+                )),
+            )),
         },
-        Err(error) => Err(ProcessingError::RelayTaskHeartbeatError(format!(
-            "State machine heartbeat relay failed.\n{:?}",
-            error
-        ))),
+        Err(error) => Err(ProcessingError::RelayTaskFailureError(
+            RemoteFailure::terminal(format!(
+                "State machine task failure relay failed.\n{:?}",
+                error
+            )),
+        )),
     }
 }
 
-#[derive(Deserialize, Clone)]
-struct EventRestModel {
-    id: String,
-    description: Option<String>,
-    event_time: String,
-    event_type: EventType,
-    raised_by: String,
+// NOTE: the expired token is sent along so the remote api only records the
+// expiry while that token is current; a fresh permit that was issued in the
+// meantime is left untouched.
+pub fn expired_permit_patch(token: &str) -> String {
+    serde_json::json!({
+        "callback_token_expired": true,
+        "expected_callback_token": token,
+    })
+    .to_string()
 }
 
-#[derive(Deserialize, Clone)]
-enum EventType {
-    #[serde(rename = "data_source")]
-    DataSource,
-    #[serde(rename = "data_pipeline")]
-    DataPipeline,
+pub async fn mark_permit_as_expired(
+    pipeline_id: &str,
+    token: &str,
+    backend: &Backend,
+    clients: &Clients,
+) -> Result<bool, ProcessingError> {
+    let endpoint_url = construct_endpoint_url(
+        &backend.endpoint_prefix,
+        &vec![String::from("pipelines"), String::from(pipeline_id)],
+    )?;
+    let headers = HashMap::from([(
+        String::from("Content-Type"),
+        String::from("application/json"),
+    )]);
+    write_conditional_rest_model(
+        endpoint_url.as_str(),
+        backend,
+        clients,
+        expired_permit_patch(token),
+        &headers,
+        "PATCH",
+    )
+    .await
 }
 
 pub async fn fetch_latest_datasource_events(
     ds_events_url: &url::Url,
-    aws_region: &str,
-    client: &Client,
+    backend: &Backend,
+    clients: &Clients,
 ) -> Result<Vec<OffsetDateTime>, ProcessingError> {
     let mut ds_events_url = ds_events_url.clone();
     match ds_events_url.path_segments_mut() {
@@ -129,8 +219,8 @@ pub async fn fetch_latest_datasource_events(
         .append_pair("descending_order", "true");
     let event_models = fetch_rest_model::<Vec<EventRestModel>, String>(
         ds_events_url.as_str(),
-        aws_region,
-        client,
+        backend,
+        clients,
         String::from(""),
         &HashMap::<String, String>::new(),
         "GET",
@@ -138,6 +228,15 @@ pub async fn fetch_latest_datasource_events(
     .await?;
     let mut event_times = vec![];
     for event in event_models {
+        if let EventType::DataPipelineFailure = event.event_type {
+            // NOTE: failures never make a dependency fresh. A blocking
+            // failure also hides every older event of the dependency, so
+            // downstream pipelines wait until it succeeds again.
+            if event.blocks_downstream.unwrap_or(false) {
+                break;
+            }
+            continue;
+        }
         let event_time = OffsetDateTime::parse(event.event_time.as_str(), &Iso8601::DEFAULT)
             .map_err(|e| {
                 ProcessingError::DatatimeParseFailure(format!("Failed to parse datetime:\n{:?}", e))
@@ -0,0 +1,427 @@
+use super::*;
+use crate::pipeline_effects::rules::{TriggerRule, TriggerRuleParameters, TriggerRuleRegistry};
+use crate::pipeline_effects::RemoteFailure;
+use std::sync::Mutex;
+use time::Duration;
+
+fn _build_data_pipeline(strict: bool, dependency_count: usize) -> DataPipeline {
+    let permit = Some(PipelineTriggerPermit {
+        content: format!("token{}", rand::random::<u32>()),
+        expires_at: None,
+        is_expired: false,
+        is_expiry_recorded: false,
+    });
+    DataPipeline {
+        id: format!("pipeline{}", rand::random::<u32>()),
+        description: String::from("test pipeline"),
+        last_success_time: Some(OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap()),
+        permit,
+        trigger_rule: match strict {
+            true => _build_trigger_rule("STRICT", dependency_count, None, None),
+            false => _build_trigger_rule("LENIENT", dependency_count, None, None),
+        },
+        dependency_urls: (0..dependency_count)
+            .map(|index| {
+                Url::parse(&format!("https://example.com/data-sources/source{}", index)).unwrap()
+            })
+            .collect(),
+        dependency_window_starts: vec![None; dependency_count],
+    }
+}
+
+fn _build_trigger_rule(
+    name: &str,
+    dependency_count: usize,
+    quorum_threshold: Option<usize>,
+    dependency_expression: Option<&str>,
+) -> Box<dyn TriggerRule> {
+    let dependencies: Vec<String> = (0..dependency_count)
+        .map(|index| format!("https://example.com/data-sources/source{}", index))
+        .collect();
+    TriggerRuleRegistry::with_builtins()
+        .build(
+            name,
+            &TriggerRuleParameters {
+                pipeline_id: "pipeline",
+                dependencies: &dependencies,
+                quorum_threshold,
+                dependency_expression,
+                rule_parameters: None,
+            },
+        )
+        .unwrap()
+}
+
+fn _event_times_for(url: &Url, fresh_sources: &[&str]) -> Vec<OffsetDateTime> {
+    let last_success_time = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+    if fresh_sources
+        .iter()
+        .any(|source| url.path().ends_with(source))
+    {
+        vec![
+            last_success_time - Duration::hours(1),
+            last_success_time + Duration::hours(1),
+        ]
+    } else {
+        vec![last_success_time - Duration::hours(1)]
+    }
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_strict_all_fresh_and_ready() {
+    let data_pipeline = _build_data_pipeline(true, 2);
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source0", "source1"])) };
+    let ready_fn = |_| async move { Ok(true) };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.pipeline_id, data_pipeline.id);
+    assert_eq!(decision.rule, "strict");
+    assert_eq!(decision.verdict, Verdict::Triggered);
+    assert_eq!(decision.is_ready, Some(true));
+    assert!(decision
+        .dependencies
+        .iter()
+        .all(|dependency| dependency.is_fresh == Some(true)));
+    assert_eq!(
+        decision.dependencies[0].newest_event_time,
+        Some(String::from("2020-09-13T13:26:40.000000000Z"))
+    );
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_strict_stale_dependency_skips_readiness() {
+    let data_pipeline = _build_data_pipeline(true, 2);
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source1"])) };
+    let ready_fn = |_| async move { panic!("readiness should not be probed") };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.verdict, Verdict::Skipped);
+    assert_eq!(decision.is_ready, None);
+    assert_eq!(decision.dependencies[0].is_fresh, Some(false));
+    assert_eq!(decision.dependencies[1].is_fresh, Some(true));
+    assert_eq!(
+        decision.reason,
+        "1 of 2 dependencies have events since the last success."
+    );
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_lenient_stops_at_first_fresh_dependency() {
+    let data_pipeline = _build_data_pipeline(false, 3);
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source0"])) };
+    let ready_fn = |_| async move { Ok(false) };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.rule, "lenient");
+    assert_eq!(decision.dependencies[0].is_fresh, Some(true));
+    assert_eq!(decision.dependencies[1].is_fresh, None);
+    assert_eq!(decision.is_ready, Some(false));
+    assert_eq!(decision.verdict, Verdict::Skipped);
+    assert_eq!(decision.reason, "Pipeline is not waiting for a trigger.");
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_quorum_stops_once_reached() {
+    let data_pipeline = DataPipeline {
+        trigger_rule: _build_trigger_rule("QUORUM", 5, Some(3), None),
+        .._build_data_pipeline(false, 5)
+    };
+    let times_fn = |url: Url| async move {
+        assert!(!url.path().ends_with("source4"));
+        Ok(_event_times_for(&url, &["source0", "source2", "source3"]))
+    };
+    let ready_fn = |_| async move { Ok(true) };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.rule, "quorum(3)");
+    assert_eq!(decision.dependencies[1].is_fresh, Some(false));
+    assert_eq!(decision.dependencies[3].is_fresh, Some(true));
+    assert_eq!(decision.dependencies[4].is_fresh, None);
+    assert_eq!(decision.verdict, Verdict::Triggered);
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_quorum_stops_once_unreachable() {
+    let data_pipeline = DataPipeline {
+        trigger_rule: _build_trigger_rule("QUORUM", 5, Some(3), None),
+        .._build_data_pipeline(false, 5)
+    };
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source4"])) };
+    let ready_fn = |_| async move { panic!("readiness should not be probed") };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.dependencies[2].is_fresh, Some(false));
+    assert_eq!(decision.dependencies[3].is_fresh, None);
+    assert_eq!(decision.dependencies[4].is_fresh, None);
+    assert_eq!(decision.verdict, Verdict::Skipped);
+    assert_eq!(
+        decision.reason,
+        "0 of 5 dependencies have events since the last success."
+    );
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_expression_stops_once_satisfied() {
+    let data_pipeline = DataPipeline {
+        trigger_rule: _build_trigger_rule(
+            "EXPRESSION",
+            3,
+            None,
+            Some("source0 OR (source1 AND source2)"),
+        ),
+        .._build_data_pipeline(false, 3)
+    };
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source0"])) };
+    let ready_fn = |_| async move { Ok(true) };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(
+        decision.rule,
+        "expression(source0 OR (source1 AND source2))"
+    );
+    assert_eq!(decision.dependencies[0].is_fresh, Some(true));
+    assert_eq!(decision.dependencies[1].is_fresh, None);
+    assert_eq!(decision.verdict, Verdict::Triggered);
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_expression_not_satisfied() {
+    let data_pipeline = DataPipeline {
+        trigger_rule: _build_trigger_rule(
+            "EXPRESSION",
+            3,
+            None,
+            Some("(source0 AND source1) OR source2"),
+        ),
+        .._build_data_pipeline(false, 3)
+    };
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source1"])) };
+    let ready_fn = |_| async move { panic!("readiness should not be probed") };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.dependencies[2].is_fresh, Some(false));
+    assert_eq!(decision.verdict, Verdict::Skipped);
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_no_dependencies() {
+    let data_pipeline = _build_data_pipeline(true, 0);
+    let times_fn = |_: Url| async move { Ok(vec![]) };
+    let ready_fn = |_| async move { Ok(true) };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.verdict, Verdict::Skipped);
+    assert_eq!(decision.reason, "Pipeline has no dependencies.");
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_missing_success_time() {
+    let mut data_pipeline = _build_data_pipeline(false, 1);
+    data_pipeline.last_success_time = None;
+    let times_fn = |_: Url| async move { Ok(vec![]) };
+    let ready_fn = |_| async move { Ok(true) };
+    let error = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap_err();
+    let decision = PipelineDecision::failed(&data_pipeline, error);
+    assert_eq!(decision.verdict, Verdict::Failed);
+    assert!(decision.reason.starts_with("MissingSuccessTime"));
+}
+
+fn _expire_permit(data_pipeline: &mut DataPipeline, is_expiry_recorded: bool) {
+    if let Some(permit) = data_pipeline.permit.as_mut() {
+        permit.expires_at = Some(OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap());
+        permit.is_expired = true;
+        permit.is_expiry_recorded = is_expiry_recorded;
+    }
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_expired_permit_skips_readiness() {
+    let mut data_pipeline = _build_data_pipeline(false, 2);
+    _expire_permit(&mut data_pipeline, false);
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source0", "source1"])) };
+    let ready_fn = |_| async move { panic!("readiness should not be probed") };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.verdict, Verdict::Skipped);
+    assert_eq!(decision.is_ready, None);
+    assert_eq!(
+        decision.reason,
+        "Pipeline trigger permit expired at 2023-11-14T22:13:20.000000000Z; readiness was not probed."
+    );
+}
+
+#[tokio::test]
+async fn handle_expired_permit_test_refreshes_and_marks() {
+    let mut data_pipeline = _build_data_pipeline(false, 1);
+    _expire_permit(&mut data_pipeline, false);
+    let token = data_pipeline.permit.as_ref().unwrap().content.clone();
+    let calls = Mutex::new(vec![]);
+    let fn_mark_permit_as_expired = |pipeline_id: String, token: String| {
+        calls
+            .lock()
+            .unwrap()
+            .push(format!("mark {} {}", pipeline_id, token));
+        async move { Ok(true) }
+    };
+    let fn_request_fresh_permit = |token: String, cause: String| {
+        calls
+            .lock()
+            .unwrap()
+            .push(format!("refresh {} ({})", token, cause));
+        async move { Ok(true) }
+    };
+    handle_expired_permit(
+        &data_pipeline,
+        true,
+        fn_mark_permit_as_expired,
+        fn_request_fresh_permit,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        calls.into_inner().unwrap(),
+        vec![
+            format!(
+                "refresh {} (Trigger permit of pipeline {} expired at 2023-11-14T22:13:20.000000000Z.)",
+                token, data_pipeline.id
+            ),
+            format!("mark {} {}", data_pipeline.id, token),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn handle_expired_permit_test_replaced_permit_is_not_an_error() {
+    let mut data_pipeline = _build_data_pipeline(false, 1);
+    _expire_permit(&mut data_pipeline, false);
+    let fn_mark_permit_as_expired = |_: String, _: String| async move { Ok(false) };
+    let fn_request_fresh_permit = |_: String, _: String| async move { Ok(true) };
+    handle_expired_permit(
+        &data_pipeline,
+        true,
+        fn_mark_permit_as_expired,
+        fn_request_fresh_permit,
+    )
+    .await
+    .unwrap();
+}
+
+#[test]
+fn expired_permit_patch_test_matches_expired_token() {
+    let patch: serde_json::Value =
+        serde_json::from_str(&remote::expired_permit_patch("token1")).unwrap();
+    assert_eq!(
+        patch,
+        serde_json::json!({
+            "callback_token_expired": true,
+            "expected_callback_token": "token1",
+        })
+    );
+}
+
+#[tokio::test]
+async fn handle_expired_permit_test_failed_refresh_is_not_marked() {
+    let mut data_pipeline = _build_data_pipeline(false, 1);
+    _expire_permit(&mut data_pipeline, false);
+    let fn_mark_permit_as_expired = |_: String, _: String| async move {
+        panic!("the expiry should not be recorded without a fresh permit");
+    };
+    let fn_request_fresh_permit = |_: String, _: String| async move {
+        Err(ProcessingError::RelayTaskFailureError(
+            RemoteFailure::transient(String::from("throttled")),
+        ))
+    };
+    let result = handle_expired_permit(
+        &data_pipeline,
+        true,
+        fn_mark_permit_as_expired,
+        fn_request_fresh_permit,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProcessingError::RelayTaskFailureError(_))
+    ));
+}
+
+#[tokio::test]
+async fn handle_expired_permit_test_refresh_is_optional() {
+    let mut data_pipeline = _build_data_pipeline(false, 1);
+    _expire_permit(&mut data_pipeline, false);
+    let marked = Mutex::new(0);
+    let fn_mark_permit_as_expired = |_: String, _: String| {
+        *marked.lock().unwrap() += 1;
+        async move { Ok(true) }
+    };
+    let fn_request_fresh_permit = |_: String, _: String| async move {
+        panic!("a fresh permit should not be requested");
+    };
+    handle_expired_permit(
+        &data_pipeline,
+        false,
+        fn_mark_permit_as_expired,
+        fn_request_fresh_permit,
+    )
+    .await
+    .unwrap();
+    assert_eq!(marked.into_inner().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn handle_expired_permit_test_skips_recorded_and_unexpired_permits() {
+    let fn_mark_permit_as_expired = |_: String, _: String| async move {
+        panic!("the expiry should not be recorded again");
+    };
+    let fn_request_fresh_permit = |_: String, _: String| async move {
+        panic!("a fresh permit should not be requested");
+    };
+    let mut recorded = _build_data_pipeline(false, 1);
+    _expire_permit(&mut recorded, true);
+    let unexpired = _build_data_pipeline(false, 1);
+    for data_pipeline in [recorded, unexpired] {
+        handle_expired_permit(
+            &data_pipeline,
+            true,
+            fn_mark_permit_as_expired,
+            fn_request_fresh_permit,
+        )
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn can_trigger_pipeline_test_event_outside_window_is_not_fresh() {
+    let mut data_pipeline = _build_data_pipeline(true, 2);
+    let last_success_time = data_pipeline.last_success_time.unwrap();
+    data_pipeline.dependency_window_starts =
+        vec![Some(last_success_time + Duration::hours(2)), None];
+    let times_fn = |url: Url| async move { Ok(_event_times_for(&url, &["source0", "source1"])) };
+    let ready_fn = |_| async move { panic!("readiness should not be probed") };
+    let decision = can_trigger_pipeline(&data_pipeline, times_fn, ready_fn)
+        .await
+        .unwrap();
+    assert_eq!(decision.verdict, Verdict::Skipped);
+    assert_eq!(decision.dependencies[0].is_fresh, Some(false));
+    assert_eq!(
+        decision.dependencies[0].window_start,
+        Some(String::from("2020-09-13T14:26:40.000000000Z"))
+    );
+    assert_eq!(
+        decision.dependencies[0].newest_event_time,
+        Some(String::from("2020-09-13T13:26:40.000000000Z"))
+    );
+    assert_eq!(decision.dependencies[1].is_fresh, Some(true));
+    assert_eq!(decision.dependencies[1].window_start, None);
+}
@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod test_response;
+
+use crate::dry_run::EffectPlan;
+use crate::events::EventProcessingError;
+use crate::pipeline_effects::decisions::{PipelineDecision, Verdict};
+use crate::pipeline_effects::{PipelineEffectsError, PipelineError};
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use simple_error::simple_error;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkippedPipeline {
+    pub pipeline_id: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HandlerResponse {
+    pub event_id: String,
+    pub replayed: bool,
+    pub pipelines_considered: usize,
+    pub triggered: Vec<String>,
+    pub skipped: Vec<SkippedPipeline>,
+    pub failed: Vec<PipelineError>,
+    pub deferred: Vec<String>,
+    // NOTE: set when the event itself was rejected with a terminal error
+    // before any pipeline was considered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<EffectPlan>,
+}
+
+impl HandlerResponse {
+    pub fn from_decisions(event_id: &str, decisions: Vec<PipelineDecision>) -> HandlerResponse {
+        let mut response = HandlerResponse {
+            pipelines_considered: decisions.len(),
+            ..HandlerResponse::_empty(event_id, false)
+        };
+        for decision in decisions {
+            match decision.verdict {
+                Verdict::Triggered => response.triggered.push(decision.pipeline_id),
+                Verdict::Skipped => response.skipped.push(SkippedPipeline {
+                    pipeline_id: decision.pipeline_id,
+                    reason: decision.reason,
+                }),
+                Verdict::Deferred => response.deferred.push(decision.pipeline_id),
+                Verdict::Failed => {
+                    if let Some(error) = decision.error {
+                        response.failed.push(PipelineError {
+                            pipeline_id: Some(decision.pipeline_id),
+                            error,
+                        })
+                    }
+                }
+            }
+        }
+        response
+    }
+
+    pub fn from_effects_error(event_id: &str, error: PipelineEffectsError) -> HandlerResponse {
+        let mut response = HandlerResponse::from_decisions(
+            event_id,
+            error
+                .decisions
+                .into_iter()
+                .filter(|decision| decision.verdict != Verdict::Failed)
+                .collect(),
+        );
+        response.pipelines_considered += error
+            .failures
+            .iter()
+            .filter(|failure| failure.pipeline_id.is_some())
+            .count();
+        response.failed = error.failures;
+        response
+    }
+
+    pub fn from_result(
+        event_id: &str,
+        result: Result<Vec<PipelineDecision>, Error>,
+    ) -> Result<HandlerResponse, Error> {
+        match result {
+            Ok(decisions) => Ok(HandlerResponse::from_decisions(event_id, decisions)),
+            Err(error) => match error.downcast::<PipelineEffectsError>() {
+                Ok(effects_error) => Ok(HandlerResponse::from_effects_error(
+                    event_id,
+                    *effects_error,
+                )),
+                Err(error) => match error.downcast::<EventProcessingError>() {
+                    Ok(event_error) if !event_error.is_retryable() => Ok(HandlerResponse {
+                        rejected: Some(format!("{:?}", event_error)),
+                        ..HandlerResponse::_empty(event_id, false)
+                    }),
+                    Ok(event_error) => Err(Error::from(event_error)),
+                    Err(error) => Err(error),
+                },
+            },
+        }
+    }
+
+    pub fn from_dry_run(
+        event_id: &str,
+        result: Result<Vec<PipelineDecision>, Error>,
+        plan: EffectPlan,
+    ) -> Result<HandlerResponse, Error> {
+        // NOTE: the plan is what a dry run was asked for, so it is kept even
+        // when the run fails with an error that is retried.
+        match HandlerResponse::from_result(event_id, result) {
+            Ok(response) => Ok(HandlerResponse {
+                plan: Some(plan),
+                ..response
+            }),
+            Err(error) => {
+                let plan_json = serde_json::to_string(&plan).unwrap_or(format!("{:?}", plan));
+                Err(Box::new(simple_error!(format!(
+                    "{}\nPlanned effects: {}",
+                    error, plan_json
+                )))
+                .into())
+            }
+        }
+    }
+
+    pub fn replayed(event_id: &str) -> HandlerResponse {
+        HandlerResponse::_empty(event_id, true)
+    }
+
+    fn _empty(event_id: &str, replayed: bool) -> HandlerResponse {
+        HandlerResponse {
+            event_id: String::from(event_id),
+            replayed,
+            pipelines_considered: 0,
+            triggered: vec![],
+            skipped: vec![],
+            failed: vec![],
+            deferred: vec![],
+            rejected: None,
+            plan: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.deferred.is_empty() && self.rejected.is_none()
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        // NOTE: deferred pipelines were never evaluated, a redelivery of
+        // the event evaluates them in a fresh invocation.
+        !self.deferred.is_empty() || self.has_transient_failures()
+    }
+
+    pub fn has_transient_failures(&self) -> bool {
+        self.failed
+            .iter()
+            .any(|failure| failure.error.is_retryable())
+    }
+
+    pub fn into_result(self) -> Result<HandlerResponse, Error> {
+        // NOTE: only transient failures fail the invocation so Lambda and
+        // SQS retry the event; the error message carries the response.
+        // Terminal failures are acknowledged, a retry would fail again.
+        if !self.is_retryable() {
+            return Ok(self);
+        }
+        Err(self.into_error())
+    }
+
+    pub fn into_sweep_result(self) -> Result<HandlerResponse, Error> {
+        // NOTE: unlike an event, a sweep is not redelivered to finish its
+        // deferred pipelines; the next scheduled sweep picks them up.
+        if !self.has_transient_failures() {
+            return Ok(self);
+        }
+        Err(self.into_error())
+    }
+
+    fn into_error(self) -> Error {
+        let response_json = serde_json::to_string(&self).unwrap_or(format!("{:?}", self));
+        Box::new(simple_error!(response_json)).into()
+    }
+}
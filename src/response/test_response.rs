@@ -0,0 +1,247 @@
+use super::*;
+use crate::dry_run::{EffectRecorder, PlannedEffect};
+use crate::pipeline_effects::{ProcessingError, RemoteFailure};
+use serde_json::json;
+
+fn _build_decision(
+    verdict: Verdict,
+    reason: &str,
+    error: Option<ProcessingError>,
+) -> PipelineDecision {
+    PipelineDecision {
+        pipeline_id: format!("pipeline{}", rand::random::<u32>()),
+        rule: String::from("strict"),
+        last_success_time: None,
+        dependencies: vec![],
+        is_ready: None,
+        verdict,
+        reason: String::from(reason),
+        error,
+    }
+}
+
+#[test]
+fn handler_response_test_groups_decisions_by_verdict() {
+    let event_id = format!("event{}", rand::random::<u32>());
+    let triggered = _build_decision(Verdict::Triggered, "ready", None);
+    let skipped = _build_decision(Verdict::Skipped, "not ready", None);
+    let response =
+        HandlerResponse::from_decisions(&event_id, vec![triggered.clone(), skipped.clone()]);
+    assert_eq!(response.event_id, event_id);
+    assert!(!response.replayed);
+    assert_eq!(response.pipelines_considered, 2);
+    assert_eq!(response.triggered, vec![triggered.pipeline_id]);
+    assert_eq!(
+        response.skipped,
+        vec![SkippedPipeline {
+            pipeline_id: skipped.pipeline_id,
+            reason: String::from("not ready"),
+        }]
+    );
+    assert!(response.failed.is_empty());
+    assert_eq!(response.clone().into_result().unwrap(), response);
+}
+
+#[test]
+fn handler_response_test_transient_failures_fail_the_invocation() {
+    let error = ProcessingError::RelayTaskHeartbeatError(RemoteFailure::transient(String::from(
+        "throttled",
+    )));
+    let failed = _build_decision(Verdict::Failed, "throttled", Some(error));
+    let response = HandlerResponse::from_decisions("event1", vec![failed.clone()]);
+    let error_message = format!("{}", response.into_result().unwrap_err());
+    let error_json = serde_json::from_str::<serde_json::Value>(&error_message).unwrap();
+    assert_eq!(
+        error_json["failed"],
+        json!([{
+            "pipeline_id": failed.pipeline_id,
+            "error": {
+                "kind": "RelayTaskHeartbeatError",
+                "message": {"reason": "throttled", "is_transient": true}
+            }
+        }])
+    );
+}
+
+#[test]
+fn handler_response_test_deferred_pipelines_fail_the_invocation() {
+    let deferred = _build_decision(Verdict::Deferred, "deadline", None);
+    let response = HandlerResponse::from_decisions("event1", vec![deferred.clone()]);
+    assert_eq!(response.deferred, vec![deferred.pipeline_id]);
+    assert!(!response.is_success());
+    assert!(response.is_retryable());
+    assert!(response.into_result().is_err());
+}
+
+#[test]
+fn handler_response_test_terminal_failures_are_acknowledged() {
+    let error = ProcessingError::PermitContentConflict(String::from("conflict"));
+    let failed = _build_decision(Verdict::Failed, "conflict", Some(error));
+    let response = HandlerResponse::from_decisions("event1", vec![failed]);
+    assert!(!response.is_success());
+    assert!(!response.is_retryable());
+    assert_eq!(response.clone().into_result().unwrap(), response);
+}
+
+#[test]
+fn handler_response_test_terminal_remote_failures_are_acknowledged() {
+    let error =
+        ProcessingError::ModelFetchFailure(RemoteFailure::terminal(String::from("404 not found")));
+    let failed = _build_decision(Verdict::Failed, "not found", Some(error));
+    let response = HandlerResponse::from_decisions("event1", vec![failed]);
+    assert!(!response.is_retryable());
+    assert_eq!(response.clone().into_result().unwrap(), response);
+}
+
+#[test]
+fn handler_response_test_sweep_acknowledges_terminal_failures_and_deferrals() {
+    let effects_error = PipelineEffectsError {
+        failures: vec![PipelineError {
+            pipeline_id: Some(String::from("misconfigured")),
+            error: ProcessingError::UnrecognizedTriggerType(String::from("badtype")),
+        }],
+        decisions: vec![_build_decision(Verdict::Deferred, "deadline", None)],
+    };
+    let response = HandlerResponse::from_result("sweep1", Err(Error::from(effects_error))).unwrap();
+    assert_eq!(
+        response.failed[0].pipeline_id.as_deref(),
+        Some("misconfigured")
+    );
+    assert!(!response.has_transient_failures());
+    assert_eq!(response.clone().into_sweep_result().unwrap(), response);
+}
+
+#[test]
+fn handler_response_test_sweep_fails_on_transient_failures() {
+    let error = ProcessingError::ModelFetchFailure(RemoteFailure::transient(String::from("503")));
+    let failed = _build_decision(Verdict::Failed, "unavailable", Some(error));
+    let response = HandlerResponse::from_decisions("sweep1", vec![failed]);
+    assert!(response.into_sweep_result().is_err());
+}
+
+#[test]
+fn handler_response_test_from_result_rejects_terminal_event_errors() {
+    let event_id = format!("event{}", rand::random::<u32>());
+    let error = EventProcessingError::EventValidationError(String::from("missing field"));
+    let response = HandlerResponse::from_result(&event_id, Err(Error::from(error))).unwrap();
+    assert!(!response.is_success());
+    assert_eq!(
+        response.rejected,
+        Some(String::from("EventValidationError(\"missing field\")"))
+    );
+    assert_eq!(response.pipelines_considered, 0);
+}
+
+#[test]
+fn handler_response_test_from_result_keeps_transient_event_errors() {
+    let error =
+        EventProcessingError::EventPersistingError(RemoteFailure::transient(String::from("503")));
+    let result = HandlerResponse::from_result("event1", Err(Error::from(error)));
+    assert_eq!(
+        format!("{}", result.unwrap_err()),
+        "EventPersistingError(RemoteFailure { reason: \"503\", is_transient: true })"
+    );
+}
+
+#[test]
+fn handler_response_test_dry_run_keeps_plan_on_transient_errors() {
+    let event_id = format!("event{}", rand::random::<u32>());
+    let recorder = EffectRecorder::new();
+    recorder.record(
+        "source1",
+        PlannedEffect::PersistEvent {
+            event_id: String::from(&event_id),
+            path: String::from("data-sources/source1/events"),
+        },
+    );
+    let plan = recorder.into_plan(&event_id);
+    let error =
+        EventProcessingError::EventPersistingError(RemoteFailure::transient(String::from("503")));
+    let result = HandlerResponse::from_dry_run(&event_id, Err(Error::from(error)), plan.clone());
+    let error_message = format!("{}", result.unwrap_err());
+    assert!(error_message.starts_with("EventPersistingError"));
+    assert!(error_message.ends_with(&serde_json::to_string(&plan).unwrap()));
+}
+
+#[test]
+fn handler_response_test_dry_run_attaches_plan() {
+    let event_id = format!("event{}", rand::random::<u32>());
+    let plan = EffectRecorder::new().into_plan(&event_id);
+    let response = HandlerResponse::from_dry_run(&event_id, Ok(vec![]), plan.clone()).unwrap();
+    assert_eq!(response.plan, Some(plan));
+}
+
+#[test]
+fn handler_response_test_replayed_serialization() {
+    assert_eq!(
+        serde_json::to_value(HandlerResponse::replayed("event1")).unwrap(),
+        json!({
+            "event_id": "event1",
+            "replayed": true,
+            "pipelines_considered": 0,
+            "triggered": [],
+            "skipped": [],
+            "failed": [],
+            "deferred": []
+        })
+    );
+}
+
+#[test]
+fn handler_response_test_from_result_downcasts_effects_error() {
+    let triggered = _build_decision(Verdict::Triggered, "ready", None);
+    let error = ProcessingError::PermitContentConflict(String::from("conflict"));
+    let failed = _build_decision(Verdict::Failed, "conflict", Some(error.clone()));
+    let effects_error = PipelineEffectsError {
+        failures: vec![PipelineError {
+            pipeline_id: Some(String::from(&failed.pipeline_id)),
+            error: error.clone(),
+        }],
+        decisions: vec![triggered.clone(), failed.clone()],
+    };
+    let response = HandlerResponse::from_result("event1", Err(Error::from(effects_error))).unwrap();
+    assert_eq!(response.pipelines_considered, 2);
+    assert_eq!(response.triggered, vec![triggered.pipeline_id]);
+    assert_eq!(
+        response.failed,
+        vec![PipelineError {
+            pipeline_id: Some(failed.pipeline_id),
+            error,
+        }]
+    );
+}
+
+#[test]
+fn handler_response_test_from_result_keeps_other_errors() {
+    let result = HandlerResponse::from_result(
+        "event1",
+        Err(Box::new(simple_error!("event could not be persisted")).into()),
+    );
+    assert_eq!(
+        format!("{}", result.unwrap_err()),
+        "event could not be persisted"
+    );
+}
+
+#[test]
+fn pipeline_effects_error_test_display() {
+    let effects_error = PipelineEffectsError {
+        failures: vec![
+            PipelineError {
+                pipeline_id: None,
+                error: ProcessingError::ModelFetchFailure(RemoteFailure::transient(String::from(
+                    "timeout",
+                ))),
+            },
+            PipelineError {
+                pipeline_id: Some(String::from("pipeline1")),
+                error: ProcessingError::UrlParseFailure(String::from("bad url")),
+            },
+        ],
+        decisions: vec![],
+    };
+    assert_eq!(
+        format!("{}", effects_error),
+        "2 pipeline effects failed:\n<event>: ModelFetchFailure(RemoteFailure { reason: \"timeout\", is_transient: true })\npipeline1: UrlParseFailure(\"bad url\")"
+    );
+}
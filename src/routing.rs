@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod test_routing;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TenantRoute {
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub creator_id_prefix: Option<String>,
+    pub endpoint_url: String,
+    pub aws_region: String,
+    #[serde(default)]
+    pub credentials_profile: Option<String>,
+    // NOTE: assumed on top of the profile's (or the default) credentials;
+    // Lambda has no shared config profiles, so this is how tenants running
+    // there get their own credentials.
+    #[serde(default)]
+    pub role_arn: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backend {
+    pub endpoint_prefix: String,
+    pub aws_region: String,
+    pub credentials_profile: Option<String>,
+    pub role_arn: Option<String>,
+}
+
+impl TenantRoute {
+    pub fn backend(&self) -> Backend {
+        Backend {
+            endpoint_prefix: String::from(&self.endpoint_url),
+            aws_region: String::from(&self.aws_region),
+            credentials_profile: self.credentials_profile.clone(),
+            role_arn: self.role_arn.clone(),
+        }
+    }
+}
+
+pub fn resolve_backend(
+    routes: &[TenantRoute],
+    default_backend: &Backend,
+    source: &str,
+    creator_id: &str,
+) -> Backend {
+    // NOTE: an exact source match is more specific than any creator id
+    // prefix; among prefixes the longest one wins.
+    if let Some(route) = routes
+        .iter()
+        .find(|route| route.source.as_deref() == Some(source))
+    {
+        return route.backend();
+    }
+    routes
+        .iter()
+        .filter_map(|route| {
+            route
+                .creator_id_prefix
+                .as_ref()
+                .filter(|prefix| creator_id.starts_with(prefix.as_str()))
+                .map(|prefix| (prefix.len(), route))
+        })
+        .max_by_key(|(prefix_len, _)| *prefix_len)
+        .map(|(_, route)| route.backend())
+        .unwrap_or_else(|| default_backend.clone())
+}
+
+pub fn distinct_backends(routes: &[TenantRoute], default_backend: &Backend) -> Vec<Backend> {
+    let mut backends = vec![default_backend.clone()];
+    for route in routes {
+        let backend = route.backend();
+        if !backends.contains(&backend) {
+            backends.push(backend);
+        }
+    }
+    backends
+}
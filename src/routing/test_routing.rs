@@ -0,0 +1,90 @@
+use super::*;
+
+fn _build_backend(name: &str) -> Backend {
+    Backend {
+        endpoint_prefix: format!("https://{}.example.com/", name),
+        aws_region: String::from("eu-west-1"),
+        credentials_profile: None,
+        role_arn: None,
+    }
+}
+
+fn _build_route(source: Option<&str>, creator_id_prefix: Option<&str>, name: &str) -> TenantRoute {
+    TenantRoute {
+        source: source.map(String::from),
+        creator_id_prefix: creator_id_prefix.map(String::from),
+        endpoint_url: format!("https://{}.example.com/", name),
+        aws_region: String::from("eu-west-1"),
+        credentials_profile: Some(format!("profile-{}", name)),
+        role_arn: None,
+    }
+}
+
+#[test]
+fn resolve_backend_test_falls_back_to_default() {
+    let default_backend = _build_backend("default");
+    let routes = vec![_build_route(Some("com.sales"), None, "sales")];
+    let creator_id = format!("pipeline{}", rand::random::<u32>());
+    assert_eq!(
+        resolve_backend(&routes, &default_backend, "com.finance", &creator_id),
+        default_backend
+    );
+}
+
+#[test]
+fn resolve_backend_test_matches_source() {
+    let default_backend = _build_backend("default");
+    let routes = vec![
+        _build_route(None, Some("sales_"), "sales_prefix"),
+        _build_route(Some("com.sales"), None, "sales"),
+    ];
+    let backend = resolve_backend(&routes, &default_backend, "com.sales", "sales_orders");
+    assert_eq!(backend.endpoint_prefix, "https://sales.example.com/");
+    assert_eq!(
+        backend.credentials_profile,
+        Some(String::from("profile-sales"))
+    );
+}
+
+#[test]
+fn resolve_backend_test_carries_role_arn() {
+    let default_backend = _build_backend("default");
+    let role_arn = format!(
+        "arn:aws:iam::123456789012:role/conductor{}",
+        rand::random::<u32>()
+    );
+    let routes = vec![TenantRoute {
+        role_arn: Some(String::from(&role_arn)),
+        credentials_profile: None,
+        .._build_route(Some("com.sales"), None, "sales")
+    }];
+    let backend = resolve_backend(&routes, &default_backend, "com.sales", "sales_orders");
+    assert_eq!(backend.role_arn, Some(role_arn));
+    assert_eq!(backend.credentials_profile, None);
+}
+
+#[test]
+fn resolve_backend_test_longest_creator_id_prefix_wins() {
+    let default_backend = _build_backend("default");
+    let routes = vec![
+        _build_route(None, Some("sales_"), "sales"),
+        _build_route(None, Some("sales_emea_"), "sales_emea"),
+    ];
+    let backend = resolve_backend(&routes, &default_backend, "com.any", "sales_emea_orders");
+    assert_eq!(backend.endpoint_prefix, "https://sales_emea.example.com/");
+    let backend = resolve_backend(&routes, &default_backend, "com.any", "sales_apac_orders");
+    assert_eq!(backend.endpoint_prefix, "https://sales.example.com/");
+}
+
+#[test]
+fn distinct_backends_test_deduplicates_routes() {
+    let default_backend = _build_backend("default");
+    let routes = vec![
+        _build_route(Some("com.sales"), None, "sales"),
+        _build_route(None, Some("sales_"), "sales"),
+        _build_route(None, Some("finance_"), "finance"),
+    ];
+    let backends = distinct_backends(&routes, &default_backend);
+    assert_eq!(backends.len(), 3);
+    assert_eq!(backends[0], default_backend);
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod test_schema;
+
+use crate::event_sources::eventbridge::RawRequest;
+use crate::events::remote::EventRestModel;
+use crate::pipeline_effects::rest_model_schemas;
+use lambda_runtime::Error;
+use schemars::{schema::RootSchema, schema_for};
+use simple_error::simple_error;
+use std::path::{Path, PathBuf};
+
+pub fn generate_schemas() -> Vec<(&'static str, RootSchema)> {
+    let mut schemas = vec![
+        ("Request", schema_for!(RawRequest)),
+        ("EventRestModel", schema_for!(EventRestModel)),
+    ];
+    schemas.extend(rest_model_schemas());
+    schemas
+}
+
+pub fn write_schemas(out_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    std::fs::create_dir_all(out_dir).map_err(|e| {
+        Box::new(simple_error!(format!(
+            "Failed to create schema output directory {:?}:\n{:?}",
+            out_dir, e
+        )))
+    })?;
+    let mut written_paths = vec![];
+    for (name, schema) in generate_schemas() {
+        let path = out_dir.join(format!("{}.schema.json", name));
+        let contents = serde_json::to_string_pretty(&schema).map_err(|e| {
+            Box::new(simple_error!(format!(
+                "Failed to serialize schema {}:\n{:?}",
+                name, e
+            )))
+        })?;
+        std::fs::write(&path, contents).map_err(|e| {
+            Box::new(simple_error!(format!(
+                "Failed to write schema {} to {:?}:\n{:?}",
+                name, &path, e
+            )))
+        })?;
+        written_paths.push(path);
+    }
+    Ok(written_paths)
+}
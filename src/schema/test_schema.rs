@@ -0,0 +1,44 @@
+use super::*;
+
+#[test]
+fn generate_schemas_test_all_contracts_present() {
+    let schemas = generate_schemas();
+    let names: Vec<&str> = schemas.iter().map(|(name, _)| *name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Request",
+            "EventRestModel",
+            "PipelineRestModel",
+            "DataSourceRestModel"
+        ]
+    );
+}
+
+#[test]
+fn generate_schemas_test_request_schema_describes_detail() {
+    let schemas = generate_schemas();
+    let request_schema = serde_json::to_value(&schemas[0].1).unwrap();
+    assert_eq!(request_schema["title"], "Request");
+    assert_eq!(
+        request_schema["properties"]["detail"]["$ref"],
+        "#/definitions/RequestDetail"
+    );
+    let required = request_schema["required"].as_array().unwrap();
+    assert!(required.contains(&serde_json::json!("source")));
+    assert!(required.contains(&serde_json::json!("detail")));
+    assert!(request_schema["definitions"]["RequestType"].is_object());
+}
+
+#[test]
+fn write_schemas_test_writes_one_file_per_contract() {
+    let out_dir = std::env::temp_dir().join(format!("conductor-schemas-{}", rand::random::<u32>()));
+    let written_paths = write_schemas(&out_dir).unwrap();
+    assert_eq!(written_paths.len(), 4);
+    for path in &written_paths {
+        let contents = std::fs::read_to_string(path).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&contents).is_ok());
+    }
+    assert!(out_dir.join("PipelineRestModel.schema.json").exists());
+    std::fs::remove_dir_all(out_dir).unwrap();
+}
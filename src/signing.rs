@@ -1,9 +1,19 @@
+use aws_config::profile::ProfileFileCredentialsProvider;
+use aws_config::sts::AssumeRoleProvider;
+use aws_credential_types::cache::{
+    CredentialsCache, ProvideCachedCredentials, SharedCredentialsCache,
+};
+use aws_credential_types::provider::SharedCredentialsProvider;
 use aws_credential_types::Credentials;
 use aws_sigv4::http_request::{sign, SignableRequest, SigningParams, SigningSettings};
+use aws_types::region::Region;
+use aws_types::SdkConfig;
 use http;
 use std::collections::HashMap;
 use std::time::SystemTime;
 
+pub const ASSUME_ROLE_SESSION_NAME: &str = "conductor";
+
 #[derive(Debug)]
 pub enum SigningError {
     RequestBuildingError(String),
@@ -18,9 +28,10 @@ pub async fn get_signed_request_for_aws<T: AsRef<[u8]>>(
     method: &str,
     body: T,
     aws_region: &str,
+    credentials: Option<&SharedCredentialsCache>,
     aws_service_name: &str,
 ) -> Result<http::Request<T>, SigningError> {
-    let creds = _get_aws_credentials_from_env().await?;
+    let creds = _get_aws_credentials(credentials).await?;
     let signing_params = _get_signing_params(
         creds.access_key_id(),
         creds.secret_access_key(),
@@ -52,9 +63,54 @@ fn _apply_sigv4_to_request<T: AsRef<[u8]>>(
     }
 }
 
-async fn _get_aws_credentials_from_env() -> Result<Credentials, SigningError> {
-    if let Some(provider) = aws_config::load_from_env().await.credentials_provider() {
-        provider.as_ref().provide_credentials().await.map_err(|e| {
+pub fn get_credentials_provider(
+    sdk_config: &SdkConfig,
+    credentials_profile: Option<&str>,
+    role_arn: Option<&str>,
+    aws_region: &str,
+) -> Option<SharedCredentialsProvider> {
+    let source_provider = match credentials_profile {
+        Some(profile) => Some(SharedCredentialsProvider::new(
+            ProfileFileCredentialsProvider::builder()
+                .profile_name(profile)
+                .build(),
+        )),
+        None => sdk_config.credentials_provider(),
+    };
+    // NOTE: the role is assumed with the profile's (or the default)
+    // credentials as its source.
+    match (role_arn, source_provider) {
+        (Some(role_arn), Some(source_provider)) => Some(SharedCredentialsProvider::new(
+            AssumeRoleProvider::builder(role_arn)
+                .session_name(ASSUME_ROLE_SESSION_NAME)
+                .region(Region::new(String::from(aws_region)))
+                .build(source_provider),
+        )),
+        (_, source_provider) => source_provider,
+    }
+}
+
+// NOTE: the cache only asks its provider for new credentials once the
+// current ones are about to expire, so an assumed role is not assumed again
+// for every request.
+pub fn get_credentials_cache(
+    sdk_config: &SdkConfig,
+    provider: SharedCredentialsProvider,
+) -> SharedCredentialsCache {
+    let mut cache_builder = CredentialsCache::lazy_builder();
+    cache_builder
+        .set_sleep(sdk_config.sleep_impl())
+        .set_time_source(sdk_config.time_source());
+    cache_builder
+        .into_credentials_cache()
+        .create_cache(provider)
+}
+
+async fn _get_aws_credentials(
+    credentials: Option<&SharedCredentialsCache>,
+) -> Result<Credentials, SigningError> {
+    if let Some(cache) = credentials {
+        cache.provide_cached_credentials().await.map_err(|e| {
             SigningError::CredentialsError(format!("Failed to obtain AWS credentials.\n{:?}", e))
         })
     } else {
@@ -0,0 +1,149 @@
+use super::*;
+use crate::config::test_config::build_sdk_config;
+use crate::entities::Event;
+use lambda_runtime::Context;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+fn _build_config() -> Config {
+    let env_vars = HashMap::from([
+        (
+            String::from("ENV_ENDPOINT_URL"),
+            String::from("http://127.0.0.1:9/"),
+        ),
+        (String::from("ENV_AWS_REGION"), String::from("eu-west-1")),
+    ]);
+    Config::from_sources(None, &env_vars, &build_sdk_config()).unwrap()
+}
+
+fn _build_lambda_event(ignored_reason: Option<String>) -> LambdaEvent<Request> {
+    let request = Request {
+        source: String::from("conductor.test"),
+        event_id: format!("event{}", rand::random::<u32>()),
+        envelope: None,
+        ignored_reason,
+        detail: RequestDetail {
+            schema_version: events::versions::CURRENT_SCHEMA_VERSION,
+            creator_type: RequestType::DataSource,
+            creator_id: format!("source{}", rand::random::<u32>()),
+            payload: RequestPayload {
+                callback_token: None,
+                success_time: None,
+                event_time: String::from("2023-09-21T10:05:00Z"),
+                failure: None,
+            },
+        },
+    };
+    LambdaEvent::new(request, Context::default())
+}
+
+fn _build_dedup_store(event_id: &str, outcome: ProcessingOutcome) -> DedupStore {
+    DedupStore::InMemory(Mutex::new(HashMap::from([(
+        String::from(event_id),
+        outcome,
+    )])))
+}
+
+#[tokio::test]
+async fn handler_test_replay_returns_recorded_response() {
+    let config = _build_config();
+    let event = _build_lambda_event(None);
+    let event_id = event.payload.event_id.clone();
+    let recorded = HandlerResponse {
+        pipelines_considered: 1,
+        triggered: vec![String::from("pipeline1")],
+        ..HandlerResponse::from_decisions(&event_id, vec![])
+    };
+    let dedup_store = _build_dedup_store(
+        &event_id,
+        ProcessingOutcome::Succeeded {
+            response: Some(Box::new(recorded.clone())),
+        },
+    );
+    let response = handler(event, &config, &dedup_store).await.unwrap();
+    assert_eq!(
+        response,
+        HandlerResponse {
+            replayed: true,
+            ..recorded
+        }
+    );
+}
+
+#[tokio::test]
+async fn handler_test_replay_without_recorded_response() {
+    let config = _build_config();
+    let event = _build_lambda_event(None);
+    let event_id = event.payload.event_id.clone();
+    let dedup_store =
+        _build_dedup_store(&event_id, ProcessingOutcome::Succeeded { response: None });
+    let response = handler(event, &config, &dedup_store).await.unwrap();
+    assert_eq!(response, HandlerResponse::replayed(&event_id));
+}
+
+#[tokio::test]
+async fn handler_test_records_response_for_replay() {
+    let config = _build_config();
+    let event = _build_lambda_event(Some(String::from("no effects")));
+    let event_id = event.payload.event_id.clone();
+    let dedup_store = DedupStore::from_config(&config);
+    let response = handler(event.clone(), &config, &dedup_store).await.unwrap();
+    assert!(!response.replayed);
+    assert_eq!(
+        dedup_store
+            .fetch_outcome(&event_id, &config.resolve_backend("", ""))
+            .await
+            .unwrap(),
+        Some(ProcessingOutcome::Succeeded {
+            response: Some(Box::new(response.clone()))
+        })
+    );
+    let replayed = handler(event, &config, &dedup_store).await.unwrap();
+    assert_eq!(
+        replayed,
+        HandlerResponse {
+            replayed: true,
+            ..response
+        }
+    );
+}
+
+#[tokio::test]
+async fn handle_deduplicated_event_test_skips_persisting_a_persisted_event() {
+    let config = _build_config();
+    let event = _build_lambda_event(None);
+    let event_id = event.payload.event_id.clone();
+    let dedup_store = _build_dedup_store(
+        &event_id,
+        ProcessingOutcome::Failed {
+            reason: String::from("earlier delivery"),
+            is_persisted: true,
+        },
+    );
+    let persisted = Mutex::new(0);
+    let fn_persist_event = |event: Event| {
+        *persisted.lock().unwrap() += 1;
+        async move { Ok(event) }
+    };
+    let fn_persist_event = &fn_persist_event;
+    let fn_process_event = |event, is_persisted| async move {
+        events::handle_lambda_event(event, None, is_persisted, fn_persist_event)
+            .await
+            .map(|_| vec![])
+    };
+    let backend = config.default_backend();
+    let response = handle_deduplicated_event(event, &backend, &dedup_store, fn_process_event)
+        .await
+        .unwrap();
+    assert!(response.is_success());
+    assert_eq!(persisted.into_inner().unwrap(), 0);
+    assert_eq!(
+        dedup_store
+            .fetch_outcome(&event_id, &backend)
+            .await
+            .unwrap(),
+        Some(ProcessingOutcome::Succeeded {
+            response: Some(Box::new(response))
+        })
+    );
+}